@@ -0,0 +1,336 @@
+use super::{Error, Result};
+use bytes::{Buf, BufMut, BytesMut};
+use de::{IncrementalDeserializer, Scanner};
+use serde::de::DeserializeOwned;
+use serde::ser::Serialize;
+use std::future::Future;
+use std::io;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Frames a stream as whitespace- or comment-separated SION values.
+///
+/// Decodes values of type `T` and encodes any `Serialize` item, each followed by a line break.
+/// As with `IncrementalDeserializer`, a number or keyword at the top level is only decoded once
+/// something follows it, or at the end of the stream.
+#[derive(Debug)]
+pub struct SionCodec<T> {
+    // bytes already taken out of the stream, for error positions
+    offset: usize,
+    scanner: Scanner,
+    output: PhantomData<fn() -> T>,
+}
+
+impl<T> SionCodec<T> {
+    pub fn new() -> Self {
+        SionCodec {
+            offset: 0,
+            scanner: Scanner::default(),
+            output: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for SionCodec<T> {
+    fn default() -> Self {
+        SionCodec::new()
+    }
+}
+
+impl<T> SionCodec<T>
+where
+    T: DeserializeOwned,
+{
+    fn decode_prefix(&mut self, src: &mut BytesMut, eof: bool) -> Result<Option<T>> {
+        match self.scanner.next_value(src, self.offset, eof)? {
+            Some((v, used)) => {
+                src.advance(used);
+                self.offset += used;
+                Ok(Some(v))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl<T> Decoder for SionCodec<T>
+where
+    T: DeserializeOwned,
+{
+    type Item = T;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<T>> {
+        self.decode_prefix(src, false)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<T>> {
+        let v = self.decode_prefix(src, true)?;
+        if v.is_none() {
+            // only whitespace and comments are left
+            self.offset += src.len();
+            src.clear();
+        }
+        Ok(v)
+    }
+}
+
+impl<T, U> Encoder<U> for SionCodec<T>
+where
+    U: Serialize,
+{
+    type Error = Error;
+
+    fn encode(&mut self, item: U, dst: &mut BytesMut) -> Result<()> {
+        let mut writer = dst.writer();
+        ::ser::to_writer(&mut writer, &item)?;
+        writer.get_mut().put_u8(b'\n');
+        Ok(())
+    }
+}
+
+/// Deserializes a value read from `reader` in chunks, like `sion_rs::from_reader`.
+///
+/// The value is parsed as soon as its last chunk has been read, and the rest of the input is
+/// read to check that only whitespace and comments follow.
+pub fn from_reader<R, T>(reader: R) -> FromReader<R, T>
+where
+    R: AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    FromReader {
+        reader,
+        buffer: IncrementalDeserializer::new(),
+        eof: false,
+        value: None,
+    }
+}
+
+/// Future returned by `from_reader`.
+#[derive(Debug)]
+pub struct FromReader<R, T> {
+    reader: R,
+    buffer: IncrementalDeserializer,
+    eof: bool,
+    // the value, once parsed, while the rest of the input is checked
+    value: Option<T>,
+}
+
+// the value is never pinned
+impl<R: Unpin, T> Unpin for FromReader<R, T> {}
+
+impl<R, T> FromReader<R, T>
+where
+    T: DeserializeOwned,
+{
+    // Ok(true) when the value has been parsed and nothing but whitespace and comments follows
+    fn advance(&mut self) -> Result<bool> {
+        if self.value.is_none() {
+            match self.buffer.next_value()? {
+                Some(v) => self.value = Some(v),
+                None if self.eof => return Err(Error::Eof),
+                None => return Ok(false),
+            }
+        }
+        if self.buffer.skip_blank()? {
+            return Err(Error::TrailingCharacters);
+        }
+        Ok(self.eof)
+    }
+}
+
+impl<R, T> Future for FromReader<R, T>
+where
+    R: AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    type Output = Result<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<T>> {
+        let this = self.get_mut();
+        let mut chunk = [0; 8192];
+        loop {
+            match this.advance() {
+                Ok(true) => return Poll::Ready(Ok(this.value.take().unwrap())),
+                Ok(false) => {}
+                Err(e) => return Poll::Ready(Err(e)),
+            }
+            let mut buf = ReadBuf::new(&mut chunk);
+            match Pin::new(&mut this.reader).poll_read(cx, &mut buf) {
+                Poll::Ready(Ok(())) if buf.filled().is_empty() => {
+                    this.eof = true;
+                    this.buffer.feed_eof();
+                }
+                Poll::Ready(Ok(())) => this.buffer.feed(buf.filled()),
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e.into())),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Serializes `value` and writes it to `writer`, flushing afterwards.
+pub fn to_writer<W, T>(writer: W, value: &T) -> ToWriter<W>
+where
+    W: AsyncWrite + Unpin,
+    T: Serialize + ?Sized,
+{
+    let (bytes, error) = match ::ser::to_vec(value) {
+        Ok(bytes) => (bytes, None),
+        Err(e) => (vec![], Some(e)),
+    };
+    ToWriter {
+        writer,
+        bytes,
+        written: 0,
+        error,
+    }
+}
+
+/// Future returned by `to_writer`.
+#[derive(Debug)]
+pub struct ToWriter<W> {
+    writer: W,
+    bytes: Vec<u8>,
+    written: usize,
+    // serialization failed before anything was written
+    error: Option<Error>,
+}
+
+impl<W> Future for ToWriter<W>
+where
+    W: AsyncWrite + Unpin,
+{
+    type Output = Result<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<()>> {
+        let this = self.get_mut();
+        if let Some(e) = this.error.take() {
+            return Poll::Ready(Err(e));
+        }
+
+        while this.written < this.bytes.len() {
+            match Pin::new(&mut this.writer).poll_write(cx, &this.bytes[this.written..]) {
+                Poll::Ready(Ok(0)) => {
+                    return Poll::Ready(Err(io::Error::from(io::ErrorKind::WriteZero).into()));
+                }
+                Poll::Ready(Ok(n)) => this.written += n,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e.into())),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        match Pin::new(&mut this.writer).poll_flush(cx) {
+            Poll::Ready(result) => Poll::Ready(result.map_err(Into::into)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_decoder() {
+        use bytes::BytesMut;
+        use codec::SionCodec;
+        use tokio_util::codec::Decoder;
+        use value::Value;
+        use Error;
+
+        let input = "[\"id\": 1, \"tags\": [\"漢字\"]]\n12 // comment\n.Date(1.5) nil";
+        let expected = ::StreamDeserializer::<Value>::new(input)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        // feed one byte at a time
+        let mut codec = SionCodec::<Value>::new();
+        let mut src = BytesMut::new();
+        let mut values = vec![];
+        for &b in input.as_bytes() {
+            src.extend_from_slice(&[b]);
+            while let Some(v) = codec.decode(&mut src).unwrap() {
+                values.push(v);
+            }
+        }
+        assert_eq!(values.len(), 3);
+        while let Some(v) = codec.decode_eof(&mut src).unwrap() {
+            values.push(v);
+        }
+        assert_eq!(values, expected);
+        assert!(src.is_empty());
+
+        let mut src = BytesMut::from(&b"[1, 2"[..]);
+        assert!(codec.decode(&mut src).unwrap().is_none());
+        match codec.decode_eof(&mut src) {
+            Err(Error::Eof) => {}
+            v => panic!("{:?}", v),
+        }
+    }
+
+    #[test]
+    fn test_encoder() {
+        use bytes::BytesMut;
+        use codec::SionCodec;
+        use tokio_util::codec::{Decoder, Encoder};
+
+        let mut codec = SionCodec::<Vec<i32>>::new();
+        let mut dst = BytesMut::new();
+        codec.encode(vec![1, 2], &mut dst).unwrap();
+        codec.encode(&[3][..], &mut dst).unwrap();
+        assert_eq!(&dst[..], &b"[1,2]\n[3]\n"[..]);
+
+        assert_eq!(codec.decode(&mut dst).unwrap(), Some(vec![1, 2]));
+        assert_eq!(codec.decode(&mut dst).unwrap(), Some(vec![3]));
+        assert_eq!(codec.decode_eof(&mut dst).unwrap(), None);
+    }
+
+    #[test]
+    fn test_async_io() {
+        use codec::{from_reader, to_writer};
+        use std::collections::BTreeMap;
+        use std::future::Future;
+        use std::sync::Arc;
+        use std::task::{Context, Poll, Wake, Waker};
+        use Error;
+
+        // reading from and writing to memory never waits, so polling once is enough
+        fn block_on<F: Future>(future: F) -> F::Output {
+            struct Noop;
+
+            impl Wake for Noop {
+                fn wake(self: Arc<Self>) {}
+            }
+
+            let waker = Waker::from(Arc::new(Noop));
+            let mut cx = Context::from_waker(&waker);
+            match Box::pin(future).as_mut().poll(&mut cx) {
+                Poll::Ready(v) => v,
+                Poll::Pending => panic!("pending"),
+            }
+        }
+
+        let mut map = BTreeMap::new();
+        map.insert("漢字".to_string(), vec![1.5, -2.0]);
+        let mut output = vec![];
+        block_on(to_writer(&mut output, &map)).unwrap();
+        assert_eq!(output, ::to_vec(&map).unwrap());
+
+        let read: BTreeMap<String, Vec<f64>> = block_on(from_reader(&output[..])).unwrap();
+        assert_eq!(read, map);
+
+        let read: u32 = block_on(from_reader(&b"12 // twelve"[..])).unwrap();
+        assert_eq!(read, 12);
+        for input in &[&b"[1] 2"[..], b"[1] [2, x"] {
+            match block_on(from_reader::<_, Vec<u32>>(&input[..])) {
+                Err(Error::TrailingCharacters) => {}
+                v => panic!("{:?}", v),
+            }
+        }
+        match block_on(from_reader::<_, u32>(&b" // nothing"[..])) {
+            Err(Error::Eof) => {}
+            v => panic!("{:?}", v),
+        }
+    }
+}
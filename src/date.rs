@@ -0,0 +1,116 @@
+use serde::de::{self, Deserialize, Deserializer, MapAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+use std::fmt;
+
+// Date is passed through serde as a newtype struct with this name, so that
+// our Serializer/Deserializer can tell it apart from a plain double.
+pub(crate) const NAME: &str = "$__sion_rs_private_Date";
+// The deserializer hands `.Date(...)` to visitors as a map with this single key.
+pub(crate) const FIELD: &str = "$__sion_rs_private_date";
+
+/// A SION `.Date(...)`, holding seconds since 1970-01-01T00:00:00Z.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Date {
+    timestamp: f64,
+}
+
+impl Date {
+    pub fn new(timestamp: f64) -> Self {
+        Date { timestamp }
+    }
+
+    pub fn timestamp(&self) -> f64 {
+        self.timestamp
+    }
+}
+
+impl From<f64> for Date {
+    fn from(timestamp: f64) -> Self {
+        Date::new(timestamp)
+    }
+}
+
+impl From<Date> for f64 {
+    fn from(date: Date) -> Self {
+        date.timestamp
+    }
+}
+
+impl Serialize for Date {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(NAME, &self.timestamp)
+    }
+}
+
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct DateVisitor;
+
+        impl<'de> Visitor<'de> for DateVisitor {
+            type Value = Date;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a SION .Date")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Date, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                match map.next_key::<String>()? {
+                    Some(ref key) if key == FIELD => Ok(Date::new(map.next_value()?)),
+                    _ => Err(de::Error::invalid_type(de::Unexpected::Map, &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(NAME, DateVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_round_trip() {
+        use super::Date;
+        use {from_str, to_string};
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Event {
+            name: String,
+            at: Date,
+        }
+
+        let event = Event {
+            name: "login".into(),
+            at: Date::new(1_500_000_000.5),
+        };
+        let expected = r#"["name":"login","at":.Date(1500000000.5)]"#;
+        assert_eq!(to_string(&event).unwrap(), expected);
+        assert_eq!(from_str::<Event>(expected).unwrap(), event);
+
+        assert_eq!(to_string(&Date::new(0.0)).unwrap(), ".Date(0.0)");
+        assert_eq!(from_str::<Date>(".Date( 0.0 )").unwrap(), Date::new(0.0));
+    }
+
+    #[test]
+    fn test_plain_number() {
+        use super::Date;
+        use {from_str, Error};
+
+        match from_str::<Date>("1500000000.5") {
+            Err(Error::ExpectedDate) => {}
+            v => panic!("{:?}", v),
+        }
+        match from_str::<Date>(".Date(0)") {
+            Err(Error::ExpectedDouble(0)) => {}
+            v => panic!("{:?}", v),
+        }
+    }
+}
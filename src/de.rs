@@ -1,5 +1,11 @@
 use super::{Error, Result};
-use serde::de::{Deserialize, DeserializeSeed, MapAccess, SeqAccess, Visitor};
+use serde::de::value::MapDeserializer;
+use serde::de::{
+    Deserialize, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess,
+    SeqAccess, VariantAccess, Visitor,
+};
+use std::io;
+use std::marker::PhantomData;
 
 #[derive(Debug)]
 pub struct Deserializer<'de> {
@@ -18,6 +24,8 @@ where
 {
     let mut deserializer = Deserializer::from_str(input);
     let v = T::deserialize(&mut deserializer)?;
+    // files usually end with a line break
+    deserializer.trim()?;
     if deserializer.input.is_empty() {
         Ok(v)
     } else {
@@ -25,6 +33,36 @@ where
     }
 }
 
+pub fn from_slice<'a, T>(input: &'a [u8]) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    from_str(to_str(input)?)
+}
+
+/// Deserializes a value read from `reader` in chunks.
+///
+/// The value is parsed as soon as its last chunk has been read, and the bytes of the value are
+/// kept in memory until then. The rest of the input is read to check that only whitespace and
+/// comments follow, without parsing it.
+pub fn from_reader<R, T>(reader: R) -> Result<T>
+where
+    R: io::Read,
+    T: DeserializeOwned,
+{
+    let mut stream = IoStreamDeserializer::new(reader);
+    let v = match stream.next() {
+        Some(v) => v?,
+        None => return Err(self::Error::Eof),
+    };
+    stream.end()?;
+    Ok(v)
+}
+
+fn to_str(input: &[u8]) -> Result<&str> {
+    ::std::str::from_utf8(input).map_err(|e| self::Error::InvalidUtf8(e.valid_up_to()))
+}
+
 impl<'de> Deserializer<'de> {
     fn peek(&self) -> Result<char> {
         self.input.chars().next().ok_or(self::Error::Eof)
@@ -46,6 +84,18 @@ impl<'de> Deserializer<'de> {
         }
     }
 
+    // the input may end in the middle of a keyword
+    fn expect_keyword(&mut self, keyword: &str, e: Error) -> Result<()> {
+        if self.input.starts_with(keyword) {
+            self.input = &self.input[keyword.len()..];
+            Ok(())
+        } else if keyword.starts_with(self.input) {
+            Err(self::Error::Eof)
+        } else {
+            Err(e)
+        }
+    }
+
     // skip until feed one of "\r", "\n", "\r\n"
     fn skip_line(&mut self) {
         enum State {
@@ -68,9 +118,7 @@ impl<'de> Deserializer<'de> {
         }
 
         let mut state = State::Normal(0);
-        let mut chars = self.input.chars();
-
-        while let Some(c) = chars.next() {
+        for c in self.input.chars() {
             if c == '\n' {
                 state.feed('\n');
                 break;
@@ -108,7 +156,7 @@ impl<'de> Deserializer<'de> {
     }
 }
 
-impl<'de, 'a> ::serde::de::Deserializer<'de> for &'a mut Deserializer<'de> {
+impl<'de> ::serde::de::Deserializer<'de> for &mut Deserializer<'de> {
     type Error = Error;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
@@ -124,30 +172,18 @@ impl<'de, 'a> ::serde::de::Deserializer<'de> for &'a mut Deserializer<'de> {
             }
             // nil
             'n' => {
-                if self.input.starts_with("nil") {
-                    self.input = &self.input["nil".len()..];
-                    visitor.visit_unit()
-                } else {
-                    Err(self::Error::ExpectedNil)
-                }
+                self.expect_keyword("nil", self::Error::ExpectedNil)?;
+                visitor.visit_unit()
             }
             // true
             't' => {
-                if self.input.starts_with("true") {
-                    self.input = &self.input["true".len()..];
-                    visitor.visit_bool(true)
-                } else {
-                    Err(self::Error::ExpectedTrue)
-                }
+                self.expect_keyword("true", self::Error::ExpectedTrue)?;
+                visitor.visit_bool(true)
             }
             // false
             'f' => {
-                if self.input.starts_with("false") {
-                    self.input = &self.input["false".len()..];
-                    visitor.visit_bool(false)
-                } else {
-                    Err(self::Error::ExpectedFalse)
-                }
+                self.expect_keyword("false", self::Error::ExpectedFalse)?;
+                visitor.visit_bool(false)
             }
             // string
             '"' => {
@@ -165,7 +201,7 @@ impl<'de, 'a> ::serde::de::Deserializer<'de> for &'a mut Deserializer<'de> {
             },
             // array or map
             '[' => {
-                // FIXME: VERY INEFFICIENT: deserialize a Value and throw it away!
+                // FIXME: INEFFICIENT: parse the first element and throw it away!
                 // create a deserializer to look ahead
                 let mut deserializer = Deserializer { input: self.input };
                 deserializer.skip()?;
@@ -179,7 +215,7 @@ impl<'de, 'a> ::serde::de::Deserializer<'de> for &'a mut Deserializer<'de> {
                 }
 
                 // this sequence has a value, parse it and discard
-                ::value::Value::deserialize(&mut deserializer)?;
+                ::serde::de::IgnoredAny::deserialize(&mut deserializer)?;
                 deserializer.trim()?;
                 match deserializer.peek()? {
                     ',' | ']' => self.deserialize_seq(visitor),
@@ -198,15 +234,19 @@ impl<'de, 'a> ::serde::de::Deserializer<'de> for &'a mut Deserializer<'de> {
                 use number::{Parser, ParseResult::*};
 
                 let mut parser = Parser::new(self.input);
-                match parser.run()? {
-                    Int(i) => {
-                        self.input = parser.get_output();
-                        visitor.visit_i64(i)
-                    },
-                    Double(f) => {
-                        self.input = parser.get_output();
-                        visitor.visit_f64(f)
+                let result = parser.run()?;
+                self.input = parser.get_output();
+                match result {
+                    Int(i) => visitor.visit_i64(i),
+                    UInt(u) => visitor.visit_u64(u),
+                    Int128(i) => visitor.visit_i128(i),
+                    UInt128(u) => visitor.visit_u128(u),
+                    #[cfg(feature = "bigint")]
+                    BigInt(b) => {
+                        let bigint = ::std::iter::once((::number::BIGINT_FIELD, b.to_string()));
+                        visitor.visit_map(MapDeserializer::<_, Error>::new(bigint))
                     },
+                    Double(f) => visitor.visit_f64(f),
                 }
             },
             // double, data, date
@@ -215,11 +255,11 @@ impl<'de, 'a> ::serde::de::Deserializer<'de> for &'a mut Deserializer<'de> {
 
                 let mut parser = Parser::new(self.input);
                 match parser.run() {
-                    Ok(Int(_)) => unreachable!(),
                     Ok(Double(f)) => {
                         self.input = parser.get_output();
                         visitor.visit_f64(f)
                     },
+                    Ok(_) => unreachable!(),
                     Err(_) => {
                         self.skip()?;
                         if self.input.starts_with("Data") {
@@ -242,21 +282,107 @@ impl<'de, 'a> ::serde::de::Deserializer<'de> for &'a mut Deserializer<'de> {
                             self.trim()?;
                             let mut parser = Parser::new(self.input);
                             match parser.run()? {
-                                Int(x) => Err(self::Error::ExpectedDouble(x)),
                                 Double(f) => {
+                                    self.input = parser.get_output();
                                     self.trim()?;
                                     self.expect(')', self::Error::ExpectedCloseBracket)?;
 
-                                    visitor.visit_f64(f)
+                                    let date = ::std::iter::once((::date::FIELD, f));
+                                    visitor.visit_map(MapDeserializer::<_, Error>::new(date))
                                 }
+                                // `.Date(1` may continue as `.Date(1.5`
+                                _ if parser.get_output().is_empty() => Err(self::Error::Eof),
+                                Int(x) => Err(self::Error::ExpectedDouble(x)),
+                                _ => Err(self::Error::Expected("Double".into())),
                             }
+                        } else if "Data".starts_with(self.input) || "Date".starts_with(self.input) {
+                            Err(self::Error::Eof)
                         } else {
                             Err(self::Error::Expected("Double, Data, or Date".into()))
                         }
                     }
                 }
             },
-            c => unreachable!("{}", c),
+            _ => Err(self::Error::Expected("a SION value".into())),
+        }
+    }
+
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_f64(visitor)
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.trim()?;
+        match self.peek()? {
+            '0'..='9' | 'N' | 'I' | '-' => {
+                let mut parser = ::number::Parser::new(self.input);
+                let f = parser.run_double()?;
+                self.input = parser.get_output();
+                visitor.visit_f64(f)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.trim()?;
+        if self.input.starts_with("nil") {
+            self.input = &self.input["nil".len()..];
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        #[cfg(feature = "arbitrary_precision")]
+        {
+            if name == ::number::NUMBER_NAME {
+                // hand over the literal as written
+                self.trim()?;
+                let len = ::number::literal_len(self.input)?;
+                let lexeme = &self.input[..len];
+                self.input = &self.input[len..];
+                return visitor.visit_map(MapDeserializer::<_, Error>::new(::std::iter::once((
+                    ::number::NUMBER_FIELD,
+                    lexeme,
+                ))));
+            }
+        }
+        if name == ::date::NAME {
+            self.trim()?;
+            if !self.input.starts_with(".Date") {
+                return if ".Date".starts_with(self.input) {
+                    Err(self::Error::Eof)
+                } else {
+                    Err(self::Error::ExpectedDate)
+                };
+            }
+            self.deserialize_any(visitor)
+        } else if name == ::raw::NAME {
+            // validate the value and hand over its text
+            self.trim()?;
+            let start = self.input;
+            ::serde::de::IgnoredAny::deserialize(&mut *self)?;
+            let raw = &start[..start.len() - self.input.len()];
+            visitor.visit_map(MapDeserializer::<_, Error>::new(::std::iter::once((
+                ::raw::FIELD,
+                raw,
+            ))))
+        } else {
+            visitor.visit_newtype_struct(self)
         }
     }
 
@@ -311,10 +437,48 @@ impl<'de, 'a> ::serde::de::Deserializer<'de> for &'a mut Deserializer<'de> {
         self.deserialize_map(visitor)
     }
 
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.trim()?;
+        match self.peek()? {
+            // unit variant
+            '"' => {
+                let (variant, output) = ::string::parse_string_literal(self.input)?;
+                self.input = output;
+                visitor.visit_enum(variant.into_deserializer())
+            }
+            // ["Variant": value]
+            '[' => {
+                self.skip()?;
+                self.trim()?;
+                if let ':' | ']' = self.peek()? {
+                    return Err(self::Error::ExpectedEnum);
+                }
+                let value = visitor.visit_enum(Enum::new(self))?;
+                self.trim()?;
+                match self.peek()? {
+                    ']' => {
+                        self.skip()?;
+                        Ok(value)
+                    }
+                    ',' => Err(self::Error::UnexpectedEnumEntry),
+                    _ => Err(self::Error::ExpectedCloseBracket),
+                }
+            }
+            _ => Err(self::Error::ExpectedEnum),
+        }
+    }
+
     forward_to_deserialize_any! {
-        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
-        bytes byte_buf option unit unit_struct newtype_struct
-        enum identifier ignored_any
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 char str string
+        bytes byte_buf unit unit_struct identifier ignored_any
     }
 }
 
@@ -386,3 +550,748 @@ impl<'a, 'de: 'a> MapAccess<'de> for CommaSeparated<'a, 'de> {
         seed.deserialize(&mut *self.deserializer)
     }
 }
+
+#[derive(Debug)]
+struct Enum<'a, 'de: 'a> {
+    deserializer: &'a mut Deserializer<'de>,
+}
+
+impl<'a, 'de: 'a> Enum<'a, 'de> {
+    fn new(deserializer: &'a mut Deserializer<'de>) -> Self {
+        Enum { deserializer }
+    }
+}
+
+impl<'a, 'de: 'a> EnumAccess<'de> for Enum<'a, 'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self)>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(&mut *self.deserializer)?;
+        self.deserializer.trim()?;
+        self.deserializer.expect(':', self::Error::ExpectedColon)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'a, 'de: 'a> VariantAccess<'de> for Enum<'a, 'de> {
+    type Error = Error;
+
+    // ["Variant": nil]
+    fn unit_variant(self) -> Result<()> {
+        Deserialize::deserialize(self.deserializer)
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self.deserializer)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserializer.trim()?;
+        ::serde::de::Deserializer::deserialize_seq(self.deserializer, visitor)
+    }
+
+    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserializer.trim()?;
+        ::serde::de::Deserializer::deserialize_map(self.deserializer, visitor)
+    }
+}
+
+/// Iterator over whitespace- or comment-separated SION values in a string.
+#[derive(Debug)]
+pub struct StreamDeserializer<'de, T> {
+    input: &'de str,
+    offset: usize,
+    failed: bool,
+    output: PhantomData<T>,
+}
+
+impl<'de, T> StreamDeserializer<'de, T>
+where
+    T: Deserialize<'de>,
+{
+    pub fn new(input: &'de str) -> Self {
+        StreamDeserializer {
+            input,
+            offset: 0,
+            failed: false,
+            output: PhantomData,
+        }
+    }
+
+    /// Bytes of the input consumed so far, i.e. the offset right after the last value.
+    pub fn byte_offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<'de, T> Iterator for StreamDeserializer<'de, T>
+where
+    T: Deserialize<'de>,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        if self.failed {
+            return None;
+        }
+
+        let mut deserializer = Deserializer::from_str(&self.input[self.offset..]);
+        let result = deserializer.trim().and_then(|_| {
+            if deserializer.input.is_empty() {
+                Ok(None)
+            } else {
+                T::deserialize(&mut deserializer).map(Some)
+            }
+        });
+        match result {
+            Ok(Some(v)) => {
+                self.offset = self.input.len() - deserializer.input.len();
+                Some(Ok(v))
+            }
+            Ok(None) => {
+                self.offset = self.input.len();
+                None
+            }
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+// Parses the first value in `bytes`, returning it along with the bytes it used up.
+// Ok(None) means that `bytes` doesn't hold a whole value yet, or, at `eof`, that
+// only whitespace and comments are left. `offset` is where `bytes` starts in the stream.
+pub(crate) fn from_prefix<T>(bytes: &[u8], offset: usize, eof: bool) -> Result<Option<(T, usize)>>
+where
+    T: DeserializeOwned,
+{
+    let (input, invalid) = match ::std::str::from_utf8(bytes) {
+        Ok(input) => (input, None),
+        Err(e) => {
+            let input = to_str(&bytes[..e.valid_up_to()])?;
+            // a character split across chunks is completed by the next one
+            if e.error_len().is_some() || eof {
+                (input, Some(offset + e.valid_up_to()))
+            } else {
+                (input, None)
+            }
+        }
+    };
+    let out_of_input = || match invalid {
+        Some(position) => Err(self::Error::InvalidUtf8(position)),
+        None if eof => Err(self::Error::Eof),
+        None => Ok(None),
+    };
+
+    let mut deserializer = Deserializer::from_str(input);
+    match deserializer.trim() {
+        Ok(()) => {}
+        Err(self::Error::Eof) => return out_of_input(),
+        Err(e) => return Err(e),
+    }
+    if deserializer.input.is_empty() {
+        return match invalid {
+            None if eof => Ok(None),
+            _ => out_of_input(),
+        };
+    }
+
+    let v = match T::deserialize(&mut deserializer) {
+        Ok(v) => v,
+        Err(self::Error::Eof) => return out_of_input(),
+        Err(e) => return Err(e),
+    };
+    // a value touching the end of the input may continue in the next chunk, e.g. `12` of `123`
+    let may_continue = input.ends_with(|c: char| c.is_alphanumeric() || c == '_' || c == '.');
+    if deserializer.input.is_empty() && may_continue && invalid.is_none() && !eof {
+        return Ok(None);
+    }
+    Ok(Some((v, input.len() - deserializer.input.len())))
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum ScanState {
+    // whitespace, or inside brackets or parentheses
+    #[default]
+    Between,
+    // a number or a keyword, or the name of `.Date(...)` and `.Data(...)`
+    Token,
+    String {
+        escape: bool,
+    },
+    Slash,
+    Comment,
+}
+
+// Finds where the first value in a growing buffer may end without parsing it, keeping track of
+// brackets, strings and comments between chunks, so that each byte is scanned once and a value
+// is parsed only after its end has arrived.
+#[derive(Debug, Default)]
+pub(crate) struct Scanner {
+    // bytes scanned so far
+    scanned: usize,
+    // of brackets and parentheses
+    depth: usize,
+    state: ScanState,
+}
+
+impl Scanner {
+    // Scans until a place where a value may end, returning false if `bytes` runs out first.
+    fn scan(&mut self, bytes: &[u8]) -> bool {
+        use self::ScanState::*;
+
+        while let Some(&b) = bytes.get(self.scanned) {
+            match self.state {
+                Token => match b {
+                    b'0'..=b'9' | b'a'..=b'z' | b'A'..=b'Z' | b'_' | b'.' | b'+' | b'-' => {}
+                    // this byte is scanned again
+                    _ => {
+                        self.state = Between;
+                        return true;
+                    }
+                },
+                String { escape: true } => self.state = String { escape: false },
+                String { escape: false } => match b {
+                    b'\\' => self.state = String { escape: true },
+                    b'"' => {
+                        self.state = Between;
+                        if self.depth == 0 {
+                            self.scanned += 1;
+                            return true;
+                        }
+                    }
+                    _ => {}
+                },
+                Slash if b == b'/' => self.state = Comment,
+                // not a comment, for the parser to report
+                Slash => {
+                    self.state = Between;
+                    return true;
+                }
+                Comment => {
+                    if b == b'\r' || b == b'\n' {
+                        self.state = Between;
+                    }
+                }
+                Between => match b {
+                    b'"' => self.state = String { escape: false },
+                    b'/' => self.state = Slash,
+                    b'[' | b'(' => self.depth += 1,
+                    b']' | b')' if self.depth <= 1 => {
+                        self.depth = 0;
+                        self.scanned += 1;
+                        return true;
+                    }
+                    b']' | b')' => self.depth -= 1,
+                    _ if self.depth > 0 || (b as char).is_ascii_whitespace() => {}
+                    _ => self.state = Token,
+                },
+            }
+            self.scanned += 1;
+        }
+        false
+    }
+
+    // Skips whitespace and comments at the front of `input`, which may end in the middle of a
+    // comment, returning the bytes skipped and whether anything else follows.
+    fn skip_blank(&mut self, input: &str, eof: bool) -> (usize, bool) {
+        let mut chars = input.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                _ if self.state == ScanState::Comment => {
+                    if c == '\r' || c == '\n' {
+                        self.state = ScanState::Between;
+                    }
+                }
+                '/' => match chars.peek() {
+                    Some(&(_, '/')) => {
+                        chars.next();
+                        self.state = ScanState::Comment;
+                    }
+                    // the next chunk may start with the second slash
+                    None if !eof => return (i, false),
+                    _ => return (i, true),
+                },
+                _ if c.is_whitespace() => {}
+                _ => return (i, true),
+            }
+        }
+        (input.len(), false)
+    }
+
+    // Like `from_prefix`, but parses only where a value may end.
+    pub(crate) fn next_value<T>(
+        &mut self,
+        bytes: &[u8],
+        offset: usize,
+        eof: bool,
+    ) -> Result<Option<(T, usize)>>
+    where
+        T: DeserializeOwned,
+    {
+        while !eof && self.scan(bytes) {
+            if let Some((v, used)) = from_prefix(bytes, offset, false)? {
+                *self = Scanner::default();
+                return Ok(Some((v, used)));
+            }
+        }
+        if !eof {
+            return Ok(None);
+        }
+        let result = from_prefix(bytes, offset, true)?;
+        *self = Scanner::default();
+        Ok(result)
+    }
+}
+
+/// Deserializer fed with chunks of input as they arrive.
+///
+/// `next_value` returns `Ok(None)` while the buffered input doesn't hold a whole value,
+/// rather than failing with `Error::Eof`. The input is scanned once as it arrives, and a value
+/// is parsed when the bracket, parenthesis or quote that closes it has been fed.
+///
+/// A number or keyword at the top level, like `12` or `nil`, could go on in the next chunk, so
+/// it is only returned once something follows it, or after `feed_eof`.
+#[derive(Debug, Default)]
+pub struct IncrementalDeserializer {
+    bytes: Vec<u8>,
+    consumed: usize,
+    // bytes dropped from the front of `bytes`
+    dropped: usize,
+    eof: bool,
+    scanner: Scanner,
+}
+
+impl IncrementalDeserializer {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) {
+        self.bytes.drain(..self.consumed);
+        self.dropped += self.consumed;
+        self.consumed = 0;
+        self.bytes.extend_from_slice(chunk);
+    }
+
+    /// Marks the end of input, so that the buffered input is parsed as it is.
+    pub fn feed_eof(&mut self) {
+        self.eof = true;
+    }
+
+    /// Bytes of the input consumed so far, i.e. the offset right after the last value.
+    pub fn byte_offset(&self) -> usize {
+        self.dropped + self.consumed
+    }
+
+    /// The next value, or `None` if more input is needed. After `feed_eof`, `None` means
+    /// that the input is over.
+    pub fn next_value<T>(&mut self) -> Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        let offset = self.byte_offset();
+        let rest = &self.bytes[self.consumed..];
+        match self.scanner.next_value(rest, offset, self.eof)? {
+            Some((v, used)) => {
+                self.consumed += used;
+                Ok(Some(v))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl IncrementalDeserializer {
+    // Drops whitespace and comments from the front of the buffered input, returning whether
+    // anything else is left.
+    pub(crate) fn skip_blank(&mut self) -> Result<bool> {
+        let rest = &self.bytes[self.consumed..];
+        let (input, invalid) = match ::std::str::from_utf8(rest) {
+            Ok(input) => (input, false),
+            // a character split across chunks is completed by the next one
+            Err(e) => (
+                to_str(&rest[..e.valid_up_to()])?,
+                e.error_len().is_some() || self.eof,
+            ),
+        };
+        let (skipped, more) = self.scanner.skip_blank(input, self.eof);
+        let position = self.byte_offset() + input.len();
+        self.consumed += skipped;
+        if !more && invalid {
+            return Err(self::Error::InvalidUtf8(position));
+        }
+        Ok(more)
+    }
+}
+
+/// Iterator over whitespace- or comment-separated SION values read from an `io::Read`.
+#[derive(Debug)]
+pub struct IoStreamDeserializer<R, T> {
+    reader: R,
+    buffer: IncrementalDeserializer,
+    eof: bool,
+    failed: bool,
+    output: PhantomData<T>,
+}
+
+impl<R, T> IoStreamDeserializer<R, T>
+where
+    R: io::Read,
+    T: DeserializeOwned,
+{
+    pub fn new(reader: R) -> Self {
+        IoStreamDeserializer {
+            reader,
+            buffer: IncrementalDeserializer::new(),
+            eof: false,
+            failed: false,
+            output: PhantomData,
+        }
+    }
+
+    /// Bytes of the input consumed so far, i.e. the offset right after the last value.
+    pub fn byte_offset(&self) -> usize {
+        self.buffer.byte_offset()
+    }
+
+    fn fill(&mut self) -> Result<()> {
+        let mut chunk = [0; 8192];
+        loop {
+            match self.reader.read(&mut chunk) {
+                Ok(0) => {
+                    self.eof = true;
+                    self.buffer.feed_eof();
+                    return Ok(());
+                }
+                Ok(n) => {
+                    self.buffer.feed(&chunk[..n]);
+                    return Ok(());
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    // Reads the rest of the input, failing if it holds anything but whitespace and comments.
+    pub(crate) fn end(&mut self) -> Result<()> {
+        loop {
+            if self.buffer.skip_blank()? {
+                return Err(self::Error::TrailingCharacters);
+            }
+            if self.eof {
+                return Ok(());
+            }
+            self.fill()?;
+        }
+    }
+}
+
+impl<R, T> Iterator for IoStreamDeserializer<R, T>
+where
+    R: io::Read,
+    T: DeserializeOwned,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        if self.failed {
+            return None;
+        }
+
+        loop {
+            let result = match self.buffer.next_value() {
+                Ok(Some(v)) => Ok(v),
+                Ok(None) if self.eof => return None,
+                Ok(None) => match self.fill() {
+                    Ok(()) => continue,
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(e),
+            };
+            self.failed = result.is_err();
+            return Some(result);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_from_slice() {
+        use {from_slice, Error};
+
+        assert_eq!(
+            from_slice::<Vec<String>>("[\"漢字\", \"😇\"]".as_bytes()).unwrap(),
+            vec!["漢字".to_string(), "😇".to_string()]
+        );
+        assert_eq!(from_slice::<&str>(b"\"borrowed\"").unwrap(), "borrowed");
+
+        match from_slice::<Vec<String>>(b"[\"ok\", \"\xe6\xbc\"]") {
+            Err(Error::InvalidUtf8(8)) => {}
+            v => panic!("{:?}", v),
+        }
+    }
+
+    // yields one byte at a time, splitting multi-byte characters
+    struct Trickle<'a>(&'a [u8]);
+
+    impl<'a> ::std::io::Read for Trickle<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> ::std::io::Result<usize> {
+            if self.0.is_empty() || buf.is_empty() {
+                return Ok(0);
+            }
+            buf[0] = self.0[0];
+            self.0 = &self.0[1..];
+            Ok(1)
+        }
+    }
+
+    #[test]
+    fn test_from_reader() {
+        use de::tests::Trickle;
+        use std::collections::BTreeMap;
+        use value::Value;
+        use {from_reader, Error};
+
+        let input = "[\"漢字\": 1, \"string😇\": 2] // comment";
+        let mut expected = BTreeMap::new();
+        expected.insert("漢字".to_string(), 1);
+        expected.insert("string😇".to_string(), 2);
+        assert_eq!(
+            from_reader::<_, BTreeMap<String, i32>>(Trickle(input.as_bytes())).unwrap(),
+            expected
+        );
+
+        match from_reader::<_, i32>(&b"\xff"[..]) {
+            Err(Error::InvalidUtf8(0)) => {}
+            v => panic!("{:?}", v),
+        }
+        let input = "1 // 漢字\n\u{3000}// more\r\n// last";
+        assert_eq!(from_reader::<_, i32>(Trickle(input.as_bytes())).unwrap(), 1);
+        match from_reader::<_, i32>(Trickle(b"1 // \xff")) {
+            Err(Error::InvalidUtf8(5)) => {}
+            v => panic!("{:?}", v),
+        }
+        // what follows isn't parsed
+        for input in &[&b"1 2"[..], b"1 ]", b"[1] x", b"1 [2, \"", b"1 /", b"1 / /"] {
+            match from_reader::<_, Value>(Trickle(input)) {
+                Err(Error::TrailingCharacters) => {}
+                v => panic!("{:?}", v),
+            }
+        }
+        match from_reader::<_, Value>(Trickle(b" // nothing")) {
+            Err(Error::Eof) => {}
+            v => panic!("{:?}", v),
+        }
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    enum Shape {
+        Empty,
+        Circle(f64),
+        Point(i64, i64),
+        Rect { width: i64, height: i64 },
+    }
+
+    #[test]
+    fn test_enum() {
+        use de::tests::Shape::*;
+        use from_str;
+
+        assert_eq!(from_str::<Shape>(r#""Empty""#).unwrap(), Empty);
+        assert_eq!(from_str::<Shape>(r#"["Empty": nil]"#).unwrap(), Empty);
+        assert_eq!(from_str::<Shape>(r#"["Circle":1.5]"#).unwrap(), Circle(1.5));
+        assert_eq!(
+            from_str::<Shape>("[ // comment\n \"Point\" : [1, -1] ]").unwrap(),
+            Point(1, -1)
+        );
+        assert_eq!(
+            from_str::<Shape>(r#"["Rect":["height":2,"width":3]]"#).unwrap(),
+            Rect {
+                width: 3,
+                height: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_enum_error() {
+        use de::tests::Shape;
+        use {from_str, Error};
+
+        match from_str::<Shape>(r#"["Circle":1.5,"Empty":nil]"#) {
+            Err(Error::UnexpectedEnumEntry) => {}
+            v => panic!("{:?}", v),
+        }
+        for input in &["[:]", "[]", "42"] {
+            match from_str::<Shape>(input) {
+                Err(Error::ExpectedEnum) => {}
+                v => panic!("{:?}", v),
+            }
+        }
+        match from_str::<Shape>(r#""Triangle""#) {
+            Err(Error::DeserializeError(ref s)) if s.contains("Triangle") => {}
+            v => panic!("{:?}", v),
+        }
+    }
+
+    #[test]
+    fn test_stream() {
+        use {Date, Error, StreamDeserializer};
+
+        let input = "1 \"two\"// comment\n[3, 4]\n.Date(5.0)\r\n";
+        let mut stream = StreamDeserializer::<::value::Value>::new(input);
+        assert_eq!(stream.byte_offset(), 0);
+        assert!(stream.next().unwrap().is_ok());
+        assert_eq!(stream.byte_offset(), 1);
+        assert!(stream.next().unwrap().is_ok());
+        assert_eq!(stream.byte_offset(), 7);
+        assert!(stream.next().unwrap().is_ok());
+        assert_eq!(stream.byte_offset(), 24);
+        assert!(stream.next().unwrap().is_ok());
+        assert_eq!(stream.byte_offset(), 35);
+        assert!(stream.next().is_none());
+        assert_eq!(stream.byte_offset(), input.len());
+
+        let dates = StreamDeserializer::<Date>::new(".Date(0.0) .Date(1.5)")
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(dates, vec![Date::new(0.0), Date::new(1.5)]);
+
+        let mut stream = StreamDeserializer::<i32>::new("1 [ 2");
+        assert_eq!(stream.next().unwrap().unwrap(), 1);
+        match stream.next() {
+            Some(Err(_)) => {}
+            v => panic!("{:?}", v),
+        }
+        assert_eq!(stream.byte_offset(), 1);
+        assert!(stream.next().is_none());
+
+        match StreamDeserializer::<Vec<i32>>::new("[1, 2").next() {
+            Some(Err(Error::Eof)) => {}
+            v => panic!("{:?}", v),
+        }
+    }
+
+    #[test]
+    fn test_io_stream() {
+        use de::tests::Trickle;
+        use value::Value;
+        use {Error, IoStreamDeserializer, StreamDeserializer};
+
+        let input = "123 -4.5e1 nil true \"漢字😇\" // comment\n\
+                     [\"k\": [1, 2]] .Data(\"AAEC\") .Date(1.0) 0x1p-2 -Infinity //";
+        let expected = StreamDeserializer::<Value>::new(input)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(expected.len(), 10);
+
+        let mut stream = IoStreamDeserializer::<_, Value>::new(Trickle(input.as_bytes()));
+        let mut values = vec![];
+        for v in &mut stream {
+            values.push(v.unwrap());
+        }
+        assert_eq!(values, expected);
+        assert_eq!(stream.byte_offset(), input.len() - 3);
+
+        let values = IoStreamDeserializer::<_, Value>::new(input.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(values, expected);
+
+        let mut stream = IoStreamDeserializer::<_, i32>::new(Trickle(b"1 \xff 2"));
+        assert_eq!(stream.next().unwrap().unwrap(), 1);
+        match stream.next() {
+            Some(Err(Error::InvalidUtf8(2))) => {}
+            v => panic!("{:?}", v),
+        }
+        assert!(stream.next().is_none());
+
+        match IoStreamDeserializer::<_, i32>::new(Trickle(b"1 12.")).nth(1) {
+            Some(Err(Error::Eof)) => {}
+            v => panic!("{:?}", v),
+        }
+    }
+
+    #[test]
+    fn test_incremental() {
+        use {Error, IncrementalDeserializer};
+
+        let mut deserializer = IncrementalDeserializer::new();
+        assert_eq!(deserializer.next_value::<i32>().unwrap(), None);
+        deserializer.feed(b"[1, ");
+        assert_eq!(deserializer.next_value::<Vec<i32>>().unwrap(), None);
+        deserializer.feed(b"2] 3");
+        assert_eq!(deserializer.next_value().unwrap(), Some(vec![1, 2]));
+        assert_eq!(deserializer.byte_offset(), 6);
+        // `3` may continue as `34`
+        assert_eq!(deserializer.next_value::<i32>().unwrap(), None);
+        deserializer.feed(b"4 \xe6\xbc");
+        assert_eq!(deserializer.next_value().unwrap(), Some(34));
+        assert_eq!(deserializer.next_value::<String>().unwrap(), None);
+        deserializer.feed_eof();
+        match deserializer.next_value::<String>() {
+            Err(Error::InvalidUtf8(10)) => {}
+            v => panic!("{:?}", v),
+        }
+
+        let mut deserializer = IncrementalDeserializer::new();
+        deserializer.feed(b"12 // comment");
+        assert_eq!(deserializer.next_value().unwrap(), Some(12));
+        assert_eq!(deserializer.next_value::<i32>().unwrap(), None);
+        deserializer.feed_eof();
+        assert_eq!(deserializer.next_value::<i32>().unwrap(), None);
+    }
+
+    #[test]
+    fn test_incremental_scan() {
+        use value::Value;
+        use IncrementalDeserializer;
+
+        // brackets in strings and comments don't close the value
+        let input = "[\"]\\\"]\": [1, // ]\n 2]] .Date (1.5)\n\"[\"";
+        let mut deserializer = IncrementalDeserializer::new();
+        let mut values = vec![];
+        for &b in input.as_bytes() {
+            deserializer.feed(&[b]);
+            while let Some(v) = deserializer.next_value::<Value>().unwrap() {
+                values.push(v);
+            }
+        }
+        assert_eq!(
+            values,
+            vec![sion!(["]\"]": [1, 2]]), Value::Date(1.5), sion!("[")]
+        );
+
+        // a large value fed in small chunks is parsed once
+        let numbers: Vec<u32> = (0..100_000).collect();
+        let input = ::to_vec(&numbers).unwrap();
+        let mut deserializer = IncrementalDeserializer::new();
+        let mut chunks = input.chunks(7);
+        let value = loop {
+            deserializer.feed(chunks.next().unwrap());
+            if let Some(v) = deserializer.next_value::<Vec<u32>>().unwrap() {
+                break v;
+            }
+        };
+        assert_eq!(value, numbers);
+        assert!(chunks.next().is_none());
+    }
+}
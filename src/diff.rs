@@ -0,0 +1,228 @@
+use std::fmt::{self, Display};
+use value::{Path, Segment, Value};
+
+/// A difference between two values, found by `diff`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    /// A map entry or array element that only the new value has.
+    Added { path: Path, value: Value },
+    /// A map entry or array element that only the old value has.
+    Removed { path: Path, value: Value },
+    /// A value that was replaced, other than a map or an array that only changed inside.
+    Changed { path: Path, old: Value, new: Value },
+}
+
+impl Change {
+    pub fn path(&self) -> &Path {
+        match self {
+            Change::Added { path, .. }
+            | Change::Removed { path, .. }
+            | Change::Changed { path, .. } => path,
+        }
+    }
+}
+
+/// Lists the changes from `old` to `new`.
+///
+/// Maps are compared by key, in the order of `old` and then of the new keys, and arrays
+/// element by element. Values are compared with `==`, so `Int(1)` and `UInt(1)` are the same.
+pub fn diff(old: &Value, new: &Value) -> Vec<Change> {
+    let mut changes = vec![];
+    diff_at(&mut Path::root(), old, new, &mut changes);
+    changes
+}
+
+fn diff_at(path: &mut Path, old: &Value, new: &Value, changes: &mut Vec<Change>) {
+    match (old, new) {
+        (Value::Map(old), Value::Map(new)) => {
+            for (key, old) in old.iter() {
+                path.push(Segment::Key(key.clone()));
+                match new.get(key) {
+                    Some(new) => diff_at(path, old, new, changes),
+                    None => changes.push(Change::Removed {
+                        path: path.clone(),
+                        value: old.clone(),
+                    }),
+                }
+                path.pop();
+            }
+            for (key, new) in new.iter().filter(|&(key, _)| !old.contains_key(key)) {
+                changes.push(Change::Added {
+                    path: path.join(Segment::Key(key.clone())),
+                    value: new.clone(),
+                });
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            for (i, (old, new)) in old.iter().zip(new.iter()).enumerate() {
+                path.push(Segment::Index(i));
+                diff_at(path, old, new, changes);
+                path.pop();
+            }
+            for (i, old) in old.iter().enumerate().skip(new.len()) {
+                changes.push(Change::Removed {
+                    path: path.join(Segment::Index(i)),
+                    value: old.clone(),
+                });
+            }
+            for (i, new) in new.iter().enumerate().skip(old.len()) {
+                changes.push(Change::Added {
+                    path: path.join(Segment::Index(i)),
+                    value: new.clone(),
+                });
+            }
+        }
+        _ => {
+            if old != new {
+                changes.push(Change::Changed {
+                    path: path.clone(),
+                    old: old.clone(),
+                    new: new.clone(),
+                });
+            }
+        }
+    }
+}
+
+/// Displays changes like a unified diff: a `@@ path @@` header for each change, followed by
+/// the pretty-printed old value on lines starting with `-` and the new one with `+`.
+///
+/// With `color`, the lines are coloured with ANSI escape codes.
+pub struct Unified<'a> {
+    changes: &'a [Change],
+    color: bool,
+}
+
+impl<'a> Unified<'a> {
+    pub fn new(changes: &'a [Change], color: bool) -> Self {
+        Unified { changes, color }
+    }
+
+    fn line(&self, f: &mut fmt::Formatter, color: &str, line: fmt::Arguments) -> fmt::Result {
+        if self.color {
+            writeln!(f, "\x1b[{}m{}\x1b[0m", color, line)
+        } else {
+            writeln!(f, "{}", line)
+        }
+    }
+
+    fn value(&self, f: &mut fmt::Formatter, sign: char, value: &Value) -> fmt::Result {
+        let color = if sign == '-' { "31" } else { "32" };
+        for line in format!("{:#}", value).lines() {
+            self.line(f, color, format_args!("{} {}", sign, line))?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Display for Unified<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for change in self.changes {
+            if change.path().segments().is_empty() {
+                self.line(f, "36", format_args!("@@ (root) @@"))?;
+            } else {
+                self.line(f, "36", format_args!("@@ {} @@", change.path()))?;
+            }
+            match change {
+                Change::Added { value, .. } => self.value(f, '+', value)?,
+                Change::Removed { value, .. } => self.value(f, '-', value)?,
+                Change::Changed { old, new, .. } => {
+                    self.value(f, '-', old)?;
+                    self.value(f, '+', new)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_diff() {
+        use diff::{diff, Change};
+        use value::Value::*;
+
+        let old = sion!([
+            "name": "server",
+            "port": 80,
+            "hosts": ["a", "b", "c"],
+            "tls": ["cert": "a.pem"],
+            1: "one",
+            [1, 2]: nil
+        ]);
+        let new = sion!([
+            "name": "server",
+            "port": 8080,
+            "hosts": ["a", "x"],
+            "tls": "off",
+            [1, 2]: true,
+            nil: .Date(0)
+        ]);
+        let changes = diff(&old, &new);
+        let paths: Vec<_> = changes.iter().map(|c| c.path().to_string()).collect();
+        assert_eq!(
+            paths,
+            vec!["/port", "/hosts/1", "/hosts/2", "/tls", "/(1)", "/([1,2])", "/(nil)"]
+        );
+        assert_eq!(
+            changes[0],
+            Change::Changed {
+                path: "/port".parse().unwrap(),
+                old: Int(80),
+                new: Int(8080),
+            }
+        );
+        match &changes[2] {
+            Change::Removed { value, .. } => assert_eq!(value, &String("c".into())),
+            c => panic!("{:?}", c),
+        }
+        match &changes[6] {
+            Change::Added { value, .. } => assert_eq!(value, &Date(0.0)),
+            c => panic!("{:?}", c),
+        }
+
+        assert!(diff(&old, &old.clone()).is_empty());
+        // maps with the same entries in another order have no changes
+        let reordered = sion!([
+            [1, 2]: nil,
+            1: "one",
+            "tls": ["cert": "a.pem"],
+            "port": 80,
+            "hosts": ["a", "b", "c"],
+            "name": "server"
+        ]);
+        assert!(diff(&old, &reordered).is_empty());
+        let changes = diff(&sion!(["a": 1, "b": 2]), &sion!(["b": 3, "a": 1]));
+        let paths: Vec<_> = changes.iter().map(|c| c.path().to_string()).collect();
+        assert_eq!(paths, vec!["/b"]);
+        assert!(diff(&sion!([1, 2]), &sion!([1u8, 2u64])).is_empty());
+        assert_eq!(diff(&sion!(1), &sion!([1])).len(), 1);
+    }
+
+    #[test]
+    fn test_unified() {
+        use diff::{diff, Unified};
+
+        let old = sion!(["port": 80, "tls": ["cert": "a.pem"]]);
+        let new = sion!(["port": 8080, "hosts": ["a"]]);
+        let changes = diff(&old, &new);
+        let expected = r#"@@ /port @@
+- 80
++ 8080
+@@ /tls @@
+- [
+-     "cert": "a.pem"
+- ]
+@@ /hosts @@
++ [
++     "a"
++ ]
+"#;
+        assert_eq!(Unified::new(&changes, false).to_string(), expected);
+
+        let changes = diff(&sion!(1), &sion!(nil));
+        let expected = "\x1b[36m@@ (root) @@\x1b[0m\n\x1b[31m- 1\x1b[0m\n\x1b[32m+ nil\x1b[0m\n";
+        assert_eq!(Unified::new(&changes, true).to_string(), expected);
+    }
+}
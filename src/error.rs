@@ -1,4 +1,5 @@
 use std::fmt::{self, Display};
+use std::io;
 
 #[derive(Debug)]
 pub enum Error {
@@ -18,6 +19,8 @@ pub enum Error {
     ExpectedComma,
     ExpectedColon,
     ExpectedDouble(i64),
+    ExpectedDate,
+    ExpectedEnum,
     ExpectedNil,
     ExpectedTrue,
     ExpectedFalse,
@@ -25,9 +28,21 @@ pub enum Error {
     UnexpectedUnderBar,
     UnexpectedSign,
     UnexpectedLineBreak(char),
+    UnexpectedEscape(char),
     UnexpectedOpenBracket,
+    UnexpectedEnumEntry,
+    IntegerOverflow,
     UnicodeConversionError(u32),
+    LoneSurrogate(u32),
     Base64DecodeError,
+    InvalidUtf8(usize),
+    Io(io::Error),
+    // a `Value` of the wrong kind or out of range for a conversion, at `path` within it
+    InvalidValue {
+        path: ::value::Path,
+        expected: &'static str,
+        found: String,
+    },
 }
 
 impl Display for Error {
@@ -50,6 +65,8 @@ impl Display for Error {
             ExpectedComma => write!(f, "expected comma"),
             ExpectedColon => write!(f, "expected colon"),
             ExpectedDouble(x) => write!(f, "expected double: {}", x),
+            ExpectedDate => write!(f, "expected .Date"),
+            ExpectedEnum => write!(f, "expected enum: a string or a map with a single entry"),
             ExpectedNil => write!(f, "expected nil"),
             ExpectedTrue => write!(f, "expected true"),
             ExpectedFalse => write!(f, "expected false"),
@@ -57,15 +74,37 @@ impl Display for Error {
             UnexpectedUnderBar => write!(f, "unexpected under bar"),
             UnexpectedSign => write!(f, "unexpected sign"),
             UnexpectedLineBreak(c) => write!(f, "unexpected line break: {}", c.escape_debug()),
+            UnexpectedEscape(c) => write!(f, "unexpected escape sequence: \\{}", c.escape_debug()),
             UnexpectedOpenBracket => write!(f, "unexpected open bracket"),
+            UnexpectedEnumEntry => write!(f, "unexpected entry: enum maps have a single entry"),
+            IntegerOverflow => write!(f, "integer literal is out of range"),
             UnicodeConversionError(x) => write!(f, "failed to convert into unicode: {}", x),
+            LoneSurrogate(x) => write!(f, "lone surrogate: \\u{{{:x}}}", x),
             Base64DecodeError => write!(f, "failed to decode base64"),
+            InvalidUtf8(position) => write!(f, "invalid UTF-8 at byte {}", position),
+            Io(e) => write!(f, "I/O error: {}", e),
+            InvalidValue {
+                path,
+                expected,
+                found,
+            } if path.segments().is_empty() => write!(f, "expected {}, found {}", expected, found),
+            InvalidValue {
+                path,
+                expected,
+                found,
+            } => write!(f, "expected {} at {}, found {}", expected, path, found),
         }
     }
 }
 
 impl ::std::error::Error for Error {}
 
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
 impl ::serde::ser::Error for Error {
     fn custom<T: Display>(v: T) -> Error {
         Error::SerializeError(v.to_string())
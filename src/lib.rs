@@ -1,22 +1,51 @@
 #[macro_use]
 extern crate serde;
-#[macro_use]
+#[cfg_attr(test, macro_use)]
 extern crate serde_derive;
 extern crate base64;
 extern crate failure;
+extern crate indexmap;
 extern crate try_from;
+#[cfg(feature = "bigint")]
+extern crate num_bigint;
+#[cfg(feature = "tokio")]
+extern crate bytes;
+#[cfg(feature = "tokio")]
+extern crate tokio;
+#[cfg(feature = "tokio")]
+extern crate tokio_util;
+
+#[macro_use]
+mod macros;
 
+#[cfg(feature = "tokio")]
+pub mod codec;
+mod date;
 pub mod de;
+pub mod diff;
 pub mod error;
 mod number;
+mod raw;
 pub mod sequence;
 pub mod ser;
 mod string;
 pub mod value;
 
-pub use de::from_str;
+pub use date::Date;
+pub use de::{
+    from_reader, from_slice, from_str, IncrementalDeserializer, IoStreamDeserializer,
+    StreamDeserializer,
+};
+pub use diff::diff;
 pub use error::Error;
-pub use ser::to_string;
+#[cfg(feature = "arbitrary_precision")]
+pub use number::Number;
+pub use raw::RawValue;
+pub use ser::{to_string, to_string_pretty, to_vec, to_vec_pretty, to_writer, to_writer_pretty};
+pub use value::{from_value, to_value, ValueRef};
+
+#[doc(hidden)]
+pub use macros::decode_base64 as __decode_base64;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -73,4 +102,80 @@ mod tests {
         let deserialized: HashMap<String, Language> = from_str(&serialized).unwrap();
         assert_eq!(deserialized, compilers);
     }
+
+    #[test]
+    fn test_enum() {
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Pair(i32, String);
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        enum Message {
+            Quit,
+            Echo(String),
+            Move(i32, i32),
+            Write { to: Option<String>, body: Pair },
+            Nothing {},
+        }
+
+        let messages = vec![
+            Message::Quit,
+            Message::Echo("hello".into()),
+            Message::Move(1, -1),
+            Message::Write {
+                to: Some("dankogai".into()),
+                body: Pair(42, "SION".into()),
+            },
+            Message::Nothing {},
+        ];
+        let expected = r#"["Quit",["Echo":"hello"],["Move":[1,-1]],["Write":["to":"dankogai","body":[42,"SION"]]],["Nothing":[:]]]"#;
+        assert_eq!(to_string(&messages).unwrap(), expected);
+        assert_eq!(from_str::<Vec<Message>>(expected).unwrap(), messages);
+    }
+
+    #[test]
+    fn test_wide_integers() {
+        #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+        struct Record {
+            hash: u64,
+            offset: i128,
+            total: u128,
+        }
+
+        let record = Record {
+            hash: 0xdead_beef_dead_beef,
+            offset: i128::MIN,
+            total: u128::MAX,
+        };
+        let expected = r#"["hash":16045690984833335023,"offset":-170141183460469231731687303715884105728,"total":340282366920938463463374607431768211455]"#;
+        assert_eq!(to_string(&record).unwrap(), expected);
+        assert_eq!(from_str::<Record>(expected).unwrap(), record);
+
+        // wider than 128 bits, but still a double
+        let input = "[340282366920938463463374607431768211456, -0x1_0000_0000_0000_0000_0000_0000_0000_0000]";
+        let expected = vec![2f64.powi(128), -(2f64.powi(128))];
+        assert_eq!(from_str::<Vec<f64>>(input).unwrap(), expected);
+        assert!(from_str::<f32>("-0").unwrap().is_sign_negative());
+    }
+
+    #[test]
+    fn test_option() {
+        #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+        struct Account {
+            id: u32,
+            email: Option<String>,
+        }
+
+        let account = Account {
+            id: 1,
+            email: Some("dankogai@example.com".into()),
+        };
+        let serialized = to_string(&account).unwrap();
+        assert_eq!(from_str::<Account>(&serialized).unwrap(), account);
+
+        let account = Account { id: 2, email: None };
+        assert_eq!(
+            from_str::<Account>(r#"["id": 2, "email": nil]"#).unwrap(),
+            account
+        );
+    }
 }
@@ -0,0 +1,272 @@
+/// Builds a `Value` from SION-like syntax.
+///
+/// Keys and values may be any SION literal, nested arrays and maps, or Rust expressions of a
+/// `Serialize` type. `.Data` takes a base64 literal as in SION, or an expression convertible
+/// into `Vec<u8>` for raw bytes. `.Date` takes the seconds since the epoch.
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate sion_rs;
+/// # fn main() {
+/// let name = "x";
+/// let value = sion!([
+///     "name": name,
+///     1: [nil, true],
+///     [:]: 1.5,
+///     "data": .Data("AQI="),
+///     "created": .Date(0.0)
+/// ]);
+/// assert_eq!(value["name"], sion!("x"));
+/// assert_eq!(value["data"], sion!(.Data(vec![1, 2])));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! sion {
+    // decide between an array and a map by the first `:` or `,` at the top level
+    (@collection ($($first:tt)*) (: $($rest:tt)*) ($($all:tt)+)) => {{
+        let mut map = $crate::sequence::Map::new();
+        sion!(@map map ($($all)+));
+        $crate::value::Value::Map(map)
+    }};
+    (@collection ($($first:tt)*) (, $($rest:tt)*) ($($all:tt)+)) => {
+        sion!(@collection ($($first)*) () ($($all)+))
+    };
+    (@collection ($($first:tt)*) () ($($all:tt)+)) => {{
+        let mut array = $crate::sequence::Array::new();
+        sion!(@array array ($($all)+));
+        $crate::value::Value::Array(array)
+    }};
+    (@collection ($($first:tt)*) ($next:tt $($rest:tt)*) ($($all:tt)+)) => {
+        sion!(@collection ($($first)* $next) ($($rest)*) ($($all)+))
+    };
+
+    // Each element takes one step if it is a single token tree, a negative number or
+    // `.Data(...)`/`.Date(...)`, so that the recursion grows with the number of elements.
+    // The tokens of anything else are munched one by one until `,`.
+    (@array $array:ident ()) => {};
+    (@array $array:ident ($elem:tt)) => {
+        $array.push(sion!($elem));
+    };
+    (@array $array:ident ($elem:tt, $($rest:tt)*)) => {
+        $array.push(sion!($elem));
+        sion!(@array $array ($($rest)*));
+    };
+    (@array $array:ident (- $elem:tt)) => {
+        $array.push(sion!(- $elem));
+    };
+    (@array $array:ident (- $elem:tt, $($rest:tt)*)) => {
+        $array.push(sion!(- $elem));
+        sion!(@array $array ($($rest)*));
+    };
+    (@array $array:ident (. $kind:ident $args:tt)) => {
+        $array.push(sion!(. $kind $args));
+    };
+    (@array $array:ident (. $kind:ident $args:tt, $($rest:tt)*)) => {
+        $array.push(sion!(. $kind $args));
+        sion!(@array $array ($($rest)*));
+    };
+    (@array $array:ident ($($rest:tt)+)) => {
+        sion!(@element $array () ($($rest)+));
+    };
+    (@element $array:ident ($($elem:tt)+) ()) => {
+        $array.push(sion!($($elem)+));
+    };
+    (@element $array:ident ($($elem:tt)+) (, $($rest:tt)*)) => {
+        $array.push(sion!($($elem)+));
+        sion!(@array $array ($($rest)*));
+    };
+    (@element $array:ident ($($elem:tt)*) ($next:tt $($rest:tt)*)) => {
+        sion!(@element $array ($($elem)* $next) ($($rest)*));
+    };
+
+    // the same for map entries with a single token tree as the key
+    (@map $map:ident ()) => {};
+    (@map $map:ident ($key:tt: $value:tt)) => {
+        $map.insert(sion!($key), sion!($value));
+    };
+    (@map $map:ident ($key:tt: $value:tt, $($rest:tt)*)) => {
+        $map.insert(sion!($key), sion!($value));
+        sion!(@map $map ($($rest)*));
+    };
+    (@map $map:ident ($key:tt: - $value:tt)) => {
+        $map.insert(sion!($key), sion!(- $value));
+    };
+    (@map $map:ident ($key:tt: - $value:tt, $($rest:tt)*)) => {
+        $map.insert(sion!($key), sion!(- $value));
+        sion!(@map $map ($($rest)*));
+    };
+    (@map $map:ident ($key:tt: . $kind:ident $args:tt)) => {
+        $map.insert(sion!($key), sion!(. $kind $args));
+    };
+    (@map $map:ident ($key:tt: . $kind:ident $args:tt, $($rest:tt)*)) => {
+        $map.insert(sion!($key), sion!(. $kind $args));
+        sion!(@map $map ($($rest)*));
+    };
+    (@map $map:ident ($($rest:tt)+)) => {
+        sion!(@key $map () ($($rest)+));
+    };
+    (@key $map:ident ($($key:tt)+) (: $($rest:tt)*)) => {
+        sion!(@value $map ($($key)+) () ($($rest)*));
+    };
+    (@key $map:ident ($($key:tt)*) ($next:tt $($rest:tt)*)) => {
+        sion!(@key $map ($($key)* $next) ($($rest)*));
+    };
+    (@value $map:ident ($($key:tt)+) ($($value:tt)+) ()) => {
+        $map.insert(sion!($($key)+), sion!($($value)+));
+    };
+    (@value $map:ident ($($key:tt)+) ($($value:tt)+) (, $($rest:tt)*)) => {
+        $map.insert(sion!($($key)+), sion!($($value)+));
+        sion!(@map $map ($($rest)*));
+    };
+    (@value $map:ident ($($key:tt)+) ($($value:tt)*) ($next:tt $($rest:tt)*)) => {
+        sion!(@value $map ($($key)+) ($($value)* $next) ($($rest)*));
+    };
+
+    (nil) => {
+        $crate::value::Value::Nil
+    };
+    (true) => {
+        $crate::value::Value::Bool(true)
+    };
+    (false) => {
+        $crate::value::Value::Bool(false)
+    };
+    ([]) => {
+        $crate::value::Value::Array($crate::sequence::Array::new())
+    };
+    ([:]) => {
+        $crate::value::Value::Map($crate::sequence::Map::new())
+    };
+    ([$($tt:tt)+]) => {
+        sion!(@collection () ($($tt)+) ($($tt)+))
+    };
+    (.Data($data:literal)) => {
+        $crate::value::Value::Data($crate::__decode_base64($data))
+    };
+    (.Data($data:expr)) => {
+        $crate::value::Value::Data(::std::convert::Into::<Vec<u8>>::into($data))
+    };
+    (.Date($timestamp:expr)) => {
+        $crate::value::Value::Date($timestamp as f64)
+    };
+    ($other:expr) => {
+        $crate::to_value(&$other).unwrap()
+    };
+}
+
+#[doc(hidden)]
+pub fn decode_base64<T: AsRef<[u8]>>(data: T) -> Vec<u8> {
+    ::base64::decode(data.as_ref()).expect("invalid base64 in .Data")
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_sion() {
+        use from_str;
+        use value::Value::{self, *};
+
+        assert_eq!(sion!(nil), Nil);
+        assert_eq!(sion!(true), Bool(true));
+        assert_eq!(sion!(-1), Int(-1));
+        assert_eq!(sion!(1.5), Double(1.5));
+        assert_eq!(sion!("漢字"), String("漢字".into()));
+        assert_eq!(sion!(.Data("wKgAAQ==")), Data(vec![192, 168, 0, 1]));
+        assert_eq!(
+            sion!(.Data(&[192, 168, 0, 1][..])),
+            Data(vec![192, 168, 0, 1])
+        );
+        assert_eq!(sion!(.Date(0)), Date(0.0));
+        let (seconds, millis) = (1_500_000_000_i64, 1_500_000_000_500_u64);
+        assert_eq!(sion!(.Date(seconds)), Date(1.5e9));
+        assert_eq!(sion!(.Date(millis / 1000)), Date(1.5e9));
+        assert_eq!(sion!([]), from_str::<Value>("[]").unwrap());
+        assert_eq!(sion!([:]), from_str::<Value>("[:]").unwrap());
+        assert_eq!(sion!([1]), from_str::<Value>("[1]").unwrap());
+        assert_eq!(
+            sion!([1, [2, [3]],]),
+            from_str::<Value>("[1, [2, [3]]]").unwrap()
+        );
+
+        let input = r#"[
+            "name": "x",
+            1: [nil, true],
+            [:]: 1.5,
+            [1, 2]: .Data("AQI="),
+            nil: .Date(1.5),
+            "nested": ["array": [], "map": ["a": -1]]
+        ]"#;
+        let value = sion!([
+            "name": "x",
+            1: [nil, true],
+            [:]: 1.5,
+            [1, 2]: .Data("AQI="),
+            nil: .Date(1.5),
+            "nested": ["array": [], "map": ["a": -1]],
+        ]);
+        assert_eq!(value, from_str::<Value>(input).unwrap());
+    }
+
+    #[test]
+    fn test_sion_large() {
+        use value::Value;
+
+        // the recursion of the macro grows with the number of entries, not of tokens
+        let value = sion!([
+            "k00": 0, "k01": -1, "k02": .Date(2), "k03": [3], "k04": nil, "k05": "5", "k06": 6.5, "k07": 7, "k08": 8, "k09": 9,
+            "k10": 0, "k11": -1, "k12": .Date(2), "k13": [3], "k14": nil, "k15": "5", "k16": 6.5, "k17": 7, "k18": 8, "k19": 9,
+            "k20": 0, "k21": -1, "k22": .Date(2), "k23": [3], "k24": nil, "k25": "5", "k26": 6.5, "k27": 7, "k28": 8, "k29": 9,
+            "k30": 0, "k31": -1, "k32": .Date(2), "k33": [3], "k34": nil, "k35": "5", "k36": 6.5, "k37": 7, "k38": 8, "k39": 9,
+            "k40": 0, "k41": -1, "k42": .Date(2), "k43": [3], "k44": nil, "k45": "5", "k46": 6.5, "k47": 7, "k48": 8, "k49": 9,
+            "k50": 0, "k51": -1, "k52": .Date(2), "k53": [3], "k54": nil, "k55": "5", "k56": 6.5, "k57": 7, "k58": 8, "k59": 9,
+            "k60": 0, "k61": -1, "k62": .Date(2), "k63": [3], "k64": nil, "k65": "5", "k66": 6.5, "k67": 7, "k68": 8, "k69": 9,
+            "k70": 0, "k71": -1, "k72": .Date(2), "k73": [3], "k74": nil, "k75": "5", "k76": 6.5, "k77": 7, "k78": 8, "k79": 9,
+            "k80": 0, "k81": -1, "k82": .Date(2), "k83": [3], "k84": nil, "k85": "5", "k86": 6.5, "k87": 7, "k88": 8, "k89": 9,
+            "k90": 0, "k91": -1, "k92": .Date(2), "k93": [3], "k94": nil, "k95": "5", "k96": 6.5, "k97": 7, "k98": 8, "k99": 9,
+        ]);
+        match value {
+            Value::Map(ref map) => assert_eq!(map.len(), 100),
+            ref v => panic!("{:?}", v),
+        }
+        assert_eq!(value["k91"], sion!(-1));
+        assert_eq!(value["k92"], Value::Date(2.0));
+
+        let value = sion!([
+            0, -1, .Date(2), [3], nil, "5", 6.5, 7, 8, 9, 0, -1, .Date(2), [3], nil, "5", 6.5, 7, 8, 9,
+            0, -1, .Date(2), [3], nil, "5", 6.5, 7, 8, 9, 0, -1, .Date(2), [3], nil, "5", 6.5, 7, 8, 9,
+            0, -1, .Date(2), [3], nil, "5", 6.5, 7, 8, 9, 0, -1, .Date(2), [3], nil, "5", 6.5, 7, 8, 9,
+            0, -1, .Date(2), [3], nil, "5", 6.5, 7, 8, 9, 0, -1, .Date(2), [3], nil, "5", 6.5, 7, 8, 9,
+            0, -1, .Date(2), [3], nil, "5", 6.5, 7, 8, 9, 0, -1, .Date(2), [3], nil, "5", 6.5, 7, 8, 9
+        ]);
+        match value {
+            Value::Array(ref array) => assert_eq!(array.len(), 100),
+            ref v => panic!("{:?}", v),
+        }
+    }
+
+    #[test]
+    fn test_sion_interpolation() {
+        use from_str;
+        use std::collections::BTreeMap;
+        use value::Value::{self, *};
+
+        let key = "name";
+        let mut tags = BTreeMap::new();
+        tags.insert("a", 1);
+        let value = sion!([
+            key: key.to_uppercase(),
+            (1 + 1): [Some(3), None::<i32>],
+            "tags": tags,
+            "value": Int(4),
+            std::f64::consts::PI.floor(): vec![1u8, 2]
+        ]);
+        let expected = r#"[
+            "name": "NAME",
+            2: [3, nil],
+            "tags": ["a": 1],
+            "value": 4,
+            3.0: [1, 2]
+        ]"#;
+        assert_eq!(value, from_str::<Value>(expected).unwrap());
+    }
+}
@@ -1,80 +1,218 @@
 use super::{Error, Result};
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+// Value serializes integers beyond 128 bits as a newtype struct with this name holding the digits,
+// and the deserializer hands them to visitors as a map with this single key
+#[cfg(feature = "bigint")]
+pub(crate) const BIGINT_NAME: &str = "$__sion_rs_private_BigInt";
+#[cfg(feature = "bigint")]
+pub(crate) const BIGINT_FIELD: &str = "$__sion_rs_private_bigint";
+// the same for the literal of a `Number`
+#[cfg(feature = "arbitrary_precision")]
+pub(crate) const NUMBER_NAME: &str = "$__sion_rs_private_Number";
+#[cfg(feature = "arbitrary_precision")]
+pub(crate) const NUMBER_FIELD: &str = "$__sion_rs_private_number";
+
+// integers take the narrowest of Int, UInt, Int128 and UInt128 that fits,
+// so that each integer has exactly one representation
+#[derive(Clone, Debug, PartialEq)]
 pub(crate) enum ParseResult {
     Int(i64),
+    UInt(u64),
+    Int128(i128),
+    UInt128(u128),
+    #[cfg(feature = "bigint")]
+    BigInt(::num_bigint::BigInt),
     Double(f64),
 }
 
-// TODO: overflow check
-fn parse_int(radix: Radix, s: &str) -> i64 {
-    use self::Radix::Hexadecimal;
-
-    let mut val = 0;
-    for c in s.chars() {
-        val *= i64::from(radix.radix());
-        match c {
-            '0' | '_' => {}
-            '1' => val += 1,
-            '2' => val += 2,
-            '3' => val += 3,
-            '4' => val += 4,
-            '5' => val += 5,
-            '6' => val += 6,
-            '7' => val += 7,
-            '8' => val += 8,
-            '9' => val += 9,
-            'a' | 'A' if radix == Hexadecimal => val += 10,
-            'b' | 'B' if radix == Hexadecimal => val += 11,
-            'c' | 'C' if radix == Hexadecimal => val += 12,
-            'd' | 'D' if radix == Hexadecimal => val += 13,
-            'e' | 'E' if radix == Hexadecimal => val += 14,
-            'f' | 'F' if radix == Hexadecimal => val += 15,
-            // TODO: return Err
-            _ => unreachable!("{:?}, {}", radix, c),
+// returns None on overflow
+fn parse_int(radix: Radix, s: &str) -> Option<u128> {
+    let mut val = 0_u128;
+    for c in digits(s) {
+        let digit = c.to_digit(radix.radix()).unwrap();
+        val = val
+            .checked_mul(u128::from(radix.radix()))?
+            .checked_add(u128::from(digit))?;
+    }
+    Some(val)
+}
+
+fn digits(s: &str) -> impl Iterator<Item = char> + '_ {
+    s.chars().filter(|&c| c != '_')
+}
+
+// round the hexadecimal significand `integer.fractional` times 2^exponent to the nearest double
+fn hexadecimal_double(integer: &str, fractional: &str, exponent: i64) -> f64 {
+    const SIGNIFICAND_BITS: i64 = 52;
+    const MIN_EXPONENT: i64 = -1074;
+
+    // accumulate up to 60 significant bits; the rest only matters for rounding
+    let mut mantissa = 0_u64;
+    let mut exponent = exponent;
+    let mut sticky = false;
+    for (c, is_fraction) in digits(integer)
+        .map(|c| (c, false))
+        .chain(digits(fractional).map(|c| (c, true)))
+    {
+        let d = u64::from(c.to_digit(16).unwrap());
+        if mantissa < 1 << 56 {
+            mantissa = mantissa * 16 + d;
+            if is_fraction {
+                exponent -= 4;
+            }
+        } else {
+            sticky |= d != 0;
+            if !is_fraction {
+                exponent += 4;
+            }
+        }
+    }
+    if mantissa == 0 {
+        return 0.0;
+    }
+
+    // the value lies in [2^top, 2^(top + 1))
+    let top = exponent + 63 - i64::from(mantissa.leading_zeros());
+    let lsb = ::std::cmp::max(top - SIGNIFICAND_BITS, MIN_EXPONENT);
+    let shift = lsb - exponent;
+    let mut significand = if shift <= 0 {
+        mantissa << -shift
+    } else if shift >= 64 {
+        0
+    } else {
+        // round half to even
+        let rest = mantissa & ((1 << shift) - 1);
+        let half = 1 << (shift - 1);
+        let significand = mantissa >> shift;
+        if rest > half || (rest == half && (sticky || significand & 1 == 1)) {
+            significand + 1
+        } else {
+            significand
+        }
+    };
+    let mut lsb = lsb;
+    if significand == 1 << (SIGNIFICAND_BITS + 1) {
+        significand >>= 1;
+        lsb += 1;
+    }
+
+    if significand < 1 << SIGNIFICAND_BITS {
+        // subnormal (lsb is MIN_EXPONENT here) or zero
+        f64::from_bits(significand)
+    } else {
+        let biased = lsb + SIGNIFICAND_BITS + 1023;
+        if biased >= 0x7ff {
+            f64::INFINITY
+        } else {
+            let fraction = significand & ((1 << SIGNIFICAND_BITS) - 1);
+            f64::from_bits((biased as u64) << SIGNIFICAND_BITS | fraction)
         }
     }
-    val
 }
 
-// FIXME: toy implementation
 impl ParseResult {
-    fn integer(sign: Sign, radix: Radix, s: &str) -> Self {
-        use self::ParseResult::Int;
+    fn integer(sign: Sign, radix: Radix, s: &str) -> Result<Self> {
+        use self::ParseResult::*;
+
+        let magnitude = match parse_int(radix, s) {
+            Some(magnitude) => magnitude,
+            None => return ParseResult::big_integer(sign, radix, s),
+        };
+        match sign {
+            self::Sign::Positive => Ok(ParseResult::from_u128(magnitude)),
+            self::Sign::Negative if magnitude <= 1 << 63 => {
+                Ok(Int((magnitude as i64).wrapping_neg()))
+            }
+            self::Sign::Negative if magnitude <= 1 << 127 => {
+                Ok(Int128((magnitude as i128).wrapping_neg()))
+            }
+            self::Sign::Negative => ParseResult::big_integer(sign, radix, s),
+        }
+    }
+
+    pub(crate) fn from_u128(v: u128) -> Self {
+        use self::ParseResult::*;
+        use std::convert::TryFrom;
+
+        if let Ok(v) = i64::try_from(v) {
+            Int(v)
+        } else if let Ok(v) = u64::try_from(v) {
+            UInt(v)
+        } else {
+            UInt128(v)
+        }
+    }
+
+    pub(crate) fn from_i128(v: i128) -> Self {
+        use std::convert::TryFrom;
+
+        if let Ok(v) = i64::try_from(v) {
+            ParseResult::Int(v)
+        } else if v < 0 {
+            ParseResult::Int128(v)
+        } else {
+            ParseResult::from_u128(v as u128)
+        }
+    }
+
+    #[cfg(feature = "bigint")]
+    fn big_integer(sign: Sign, radix: Radix, s: &str) -> Result<Self> {
+        use num_bigint::BigInt;
+
+        let digits: String = digits(s).collect();
+        let magnitude = BigInt::parse_bytes(digits.as_bytes(), radix.radix()).unwrap();
         if sign == self::Sign::Positive {
-            Int(parse_int(radix, s))
+            Ok(ParseResult::BigInt(magnitude))
         } else {
-            Int(-parse_int(radix, s))
+            Ok(ParseResult::BigInt(-magnitude))
         }
     }
 
+    #[cfg(not(feature = "bigint"))]
+    fn big_integer(_sign: Sign, _radix: Radix, _s: &str) -> Result<Self> {
+        Err(self::Error::IntegerOverflow)
+    }
+
     fn double(
         sign: Sign,
         radix: Radix,
         integer: Option<&str>,
-        fractional: &str,
+        fractional: Option<&str>,
         exponent_sign: Sign,
         exponent: &str,
     ) -> Self {
         use self::ParseResult::Double;
 
-        let integer = integer
-            .map(|integer| parse_int(radix, integer) as f64)
-            .unwrap_or(0.0);
-        let fractional = parse_int(radix, fractional) as f64;
-        let exponent = parse_int(radix, exponent) as i32;
+        let integer = integer.unwrap_or("0");
+        let fractional = fractional.unwrap_or("");
 
-        let base = if fractional != 0.0 {
-            integer
-                + fractional
-                    / f64::from(radix.radix()).powf(fractional.log(f64::from(radix.radix())).ceil())
-        } else {
-            integer
-        };
-        let v = if exponent_sign == self::Sign::Positive {
-            base * f64::from(radix.exponent()).powi(exponent)
-        } else {
-            base * f64::from(radix.exponent()).powi(-exponent)
+        let v = match radix {
+            self::Radix::Decimal => {
+                // the standard library rounds decimal literals correctly
+                let mut literal: String = digits(integer).collect();
+                literal.push('.');
+                literal.extend(digits(fractional));
+                literal.push('0');
+                literal.push('e');
+                if exponent_sign == self::Sign::Negative {
+                    literal.push('-');
+                }
+                literal.extend(digits(exponent));
+                literal.parse().unwrap()
+            }
+            self::Radix::Hexadecimal => {
+                // anything beyond this saturates to zero or infinity anyway
+                const LIMIT: i64 = 1 << 32;
+                let exponent = digits(exponent).fold(0_i64, |acc, c| {
+                    ::std::cmp::min(acc * 10 + i64::from(c.to_digit(10).unwrap()), LIMIT)
+                });
+                let exponent = if exponent_sign == self::Sign::Positive {
+                    exponent
+                } else {
+                    -exponent
+                };
+                hexadecimal_double(integer, fractional, exponent)
+            }
         };
         if sign == self::Sign::Positive {
             Double(v)
@@ -112,14 +250,6 @@ impl Radix {
             Hexadecimal => c == 'p' || c == 'P',
         }
     }
-
-    fn exponent(&self) -> u32 {
-        use self::Radix::*;
-        match self {
-            Decimal => 10,
-            Hexadecimal => 2,
-        }
-    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -148,7 +278,7 @@ pub(crate) enum ParserState {
         sign: Sign,
         radix: Radix,
         integer: Option<Literal>,
-        fraction: Literal,
+        fraction: Option<Literal>,
         exponent_sign: Option<Sign>,
         exponent: Option<Literal>,
     },
@@ -156,17 +286,38 @@ pub(crate) enum ParserState {
 }
 
 impl ParserState {
-    fn get_result(self, s: &str) -> Result<ParseResult> {
+    fn get_result(self, s: &str, as_double: bool) -> Result<ParseResult> {
         use self::ParserState::*;
 
         match self {
             Start | NegStart | HexadecimalStart { .. } | Done => unreachable!(),
+            LeadingZero { sign, .. } if as_double => Ok(ParseResult::double(
+                sign,
+                self::Radix::Decimal,
+                None,
+                None,
+                self::Sign::Positive,
+                "0",
+            )),
             LeadingZero { .. } => Ok(ParseResult::Int(0)),
+            // rounds integers of any size to the nearest double
             IntOrDouble {
                 sign,
                 radix,
                 integer: (start, end),
-            } => Ok(ParseResult::integer(sign, radix, &s[start..end])),
+            } if as_double => Ok(ParseResult::double(
+                sign,
+                radix,
+                Some(&s[start..end]),
+                None,
+                self::Sign::Positive,
+                "0",
+            )),
+            IntOrDouble {
+                sign,
+                radix,
+                integer: (start, end),
+            } => ParseResult::integer(sign, radix, &s[start..end]),
             Double {
                 sign,
                 radix,
@@ -176,7 +327,7 @@ impl ParserState {
                 sign,
                 radix,
                 integer.map(|integer| &s[integer.0..integer.1]),
-                &s[fraction.0..fraction.1],
+                Some(&s[fraction.0..fraction.1]),
                 self::Sign::Positive,
                 "0",
             )),
@@ -192,7 +343,7 @@ impl ParserState {
                 sign,
                 radix,
                 integer.map(|integer| &s[integer.0..integer.1]),
-                &s[fraction.0..fraction.1],
+                fraction.map(|fraction| &s[fraction.0..fraction.1]),
                 exponent_sign.unwrap_or(self::Sign::Positive),
                 &s[exponent.0..exponent.1],
             )),
@@ -206,6 +357,8 @@ pub(crate) struct Parser<'de> {
     original: &'de str,
     input: &'de str,
     state: ParserState,
+    // parse integers as doubles too
+    as_double: bool,
 }
 
 impl<'de> Parser<'de> {
@@ -214,6 +367,7 @@ impl<'de> Parser<'de> {
             original: input,
             input,
             state: self::ParserState::Start,
+            as_double: false,
         }
     }
 
@@ -235,6 +389,8 @@ impl<'de> Parser<'de> {
         if self.input.starts_with(expected) {
             self.input = &self.input[expected.len()..];
             Ok(())
+        } else if expected.starts_with(self.input) {
+            Err(self::Error::Eof)
         } else {
             Err(e)
         }
@@ -244,9 +400,7 @@ impl<'de> Parser<'de> {
         use self::ParserState::*;
         use self::Radix::*;
 
-        let mut char_indices = self.input.char_indices();
-
-        while let Some((i, c)) = char_indices.next() {
+        for (i, c) in self.input.char_indices() {
             match ::std::mem::replace(&mut self.state, Done) {
                 Start => {
                     if c == '0' {
@@ -255,7 +409,7 @@ impl<'de> Parser<'de> {
                             position: i,
                         };
                         self.skip()?;
-                    } else if c.is_digit(10) {
+                    } else if c.is_ascii_digit() {
                         self.state = IntOrDouble {
                             sign: self::Sign::Positive,
                             radix: Decimal,
@@ -264,10 +418,10 @@ impl<'de> Parser<'de> {
                         self.skip()?;
                     } else if c == 'N' {
                         self.expect("NaN", self::Error::ExpectedNaN)?;
-                        return Ok(ParseResult::Double(::std::f64::NAN));
+                        return Ok(ParseResult::Double(f64::NAN));
                     } else if c == 'I' {
                         self.expect("Infinity", self::Error::ExpectedInfinity)?;
-                        return Ok(ParseResult::Double(::std::f64::INFINITY));
+                        return Ok(ParseResult::Double(f64::INFINITY));
                     } else if c == '-' {
                         self.state = NegStart;
                         self.skip()?;
@@ -297,15 +451,26 @@ impl<'de> Parser<'de> {
                             fraction: None,
                         };
                         self.skip()?;
-                    } else if c.is_digit(10) {
+                    } else if c.is_ascii_digit() {
                         self.state = IntOrDouble {
                             sign,
                             radix: Decimal,
                             integer: (position, position + '0'.len_utf8() + c.len_utf8()),
                         };
                         self.skip()?;
+                    } else if Decimal.is_exponent(c) {
+                        self.state = DoubleExponent {
+                            sign,
+                            radix: Decimal,
+                            integer: Some((position, position + '0'.len_utf8())),
+                            fraction: None,
+                            exponent_sign: None,
+                            exponent: None,
+                        };
+                        self.skip()?;
                     } else {
-                        return LeadingZero { sign, position }.get_result(self.original);
+                        return LeadingZero { sign, position }
+                            .get_result(self.original, self.as_double);
                     }
                 }
                 NegStart => {
@@ -314,7 +479,8 @@ impl<'de> Parser<'de> {
                             sign: self::Sign::Negative,
                             position: i,
                         };
-                    } else if c.is_digit(10) {
+                        self.skip()?;
+                    } else if c.is_ascii_digit() {
                         self.state = IntOrDouble {
                             sign: self::Sign::Negative,
                             radix: Decimal,
@@ -323,7 +489,7 @@ impl<'de> Parser<'de> {
                         self.skip()?;
                     } else if c == 'I' {
                         self.expect("Infinity", self::Error::ExpectedInfinity)?;
-                        return Ok(ParseResult::Double(::std::f64::NEG_INFINITY));
+                        return Ok(ParseResult::Double(f64::NEG_INFINITY));
                     } else if c == '.' {
                         self.state = Double {
                             sign: self::Sign::Negative,
@@ -337,7 +503,7 @@ impl<'de> Parser<'de> {
                     }
                 }
                 HexadecimalStart { sign } => {
-                    if c.is_digit(16) {
+                    if c.is_ascii_hexdigit() {
                         self.state = IntOrDouble {
                             sign,
                             radix: Hexadecimal,
@@ -368,12 +534,22 @@ impl<'de> Parser<'de> {
                             fraction: None,
                         };
                         self.skip()?;
+                    } else if radix.is_exponent(c) {
+                        self.state = DoubleExponent {
+                            sign,
+                            radix,
+                            integer: Some((start, end)),
+                            fraction: None,
+                            exponent_sign: None,
+                            exponent: None,
+                        };
+                        self.skip()?;
                     } else {
                         return IntOrDouble {
                             sign,
                             radix,
                             integer: (start, end),
-                        }.get_result(self.original);
+                        }.get_result(self.original, self.as_double);
                     }
                 }
                 Double {
@@ -413,7 +589,7 @@ impl<'de> Parser<'de> {
                             return Err(self::Error::UnexpectedUnderBar);
                         }
                     } else if radix.is_exponent(c) {
-                        if let Some(fraction) = fraction {
+                        if fraction.is_some() {
                             self.state = DoubleExponent {
                                 sign,
                                 radix,
@@ -432,7 +608,7 @@ impl<'de> Parser<'de> {
                             radix,
                             integer,
                             fraction,
-                        }.get_result(self.original);
+                        }.get_result(self.original, self.as_double);
                     }
                 }
                 DoubleExponent {
@@ -461,7 +637,7 @@ impl<'de> Parser<'de> {
                         } else {
                             return Err(self::Error::UnexpectedSign);
                         }
-                    } else if c.is_digit(10) {
+                    } else if c.is_ascii_digit() {
                         if let Some((start, end)) = exponent {
                             self.state = DoubleExponent {
                                 sign,
@@ -505,7 +681,7 @@ impl<'de> Parser<'de> {
                             fraction,
                             exponent_sign,
                             exponent,
-                        }.get_result(self.original);
+                        }.get_result(self.original, self.as_double);
                     }
                 }
                 // TODO: handle better
@@ -513,7 +689,262 @@ impl<'de> Parser<'de> {
             }
         }
 
-        ::std::mem::replace(&mut self.state, Done).get_result(self.original)
+        // ran out of input
+        match ::std::mem::replace(&mut self.state, Done) {
+            Start
+            | NegStart
+            | HexadecimalStart { .. }
+            | Double { fraction: None, .. }
+            | DoubleExponent { exponent: None, .. } => Err(self::Error::Eof),
+            state => state.get_result(self.original, self.as_double),
+        }
+    }
+
+    // for a target of f32 or f64, which an integer beyond 128 bits still fits in
+    pub(crate) fn run_double(&mut self) -> Result<f64> {
+        self.as_double = true;
+        match self.run()? {
+            ParseResult::Double(f) => Ok(f),
+            _ => unreachable!(),
+        }
+    }
+}
+
+// the end of the number literal at the start of `input`,
+// which may be an integer too large to be parsed
+#[cfg(feature = "arbitrary_precision")]
+pub(crate) fn literal_len(input: &str) -> Result<usize> {
+    let mut parser = Parser::new(input);
+    match parser.run() {
+        Ok(_) | Err(Error::IntegerOverflow) => Ok(input.len() - parser.get_output().len()),
+        Err(e) => Err(e),
+    }
+}
+
+/// A SION number literal, kept as written.
+///
+/// Deserializing a `Number` keeps the digits and the radix of the literal, so that the
+/// serializer writes it back unchanged and no precision is lost in between. Equality
+/// compares the literals, so `1.50` and `1.5` differ.
+///
+/// `Value` doesn't keep literals: converting a `Number` into a `Value` parses it into an
+/// integer or a double like any other number, so `0xFF` comes back as `255`.
+#[cfg(feature = "arbitrary_precision")]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Number {
+    lexeme: Box<str>,
+}
+
+#[cfg(feature = "arbitrary_precision")]
+impl Number {
+    pub fn as_str(&self) -> &str {
+        &self.lexeme
+    }
+
+    fn parse(&self) -> Option<ParseResult> {
+        Parser::new(&self.lexeme).run().ok()
+    }
+
+    /// The value if it is an integer that fits in `i64`.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.parse()? {
+            ParseResult::Int(i) => Some(i),
+            _ => None,
+        }
+    }
+
+    /// The value if it is an integer that fits in `u64`.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self.parse()? {
+            ParseResult::Int(i) if i >= 0 => Some(i as u64),
+            ParseResult::UInt(u) => Some(u),
+            _ => None,
+        }
+    }
+
+    /// The nearest double to the value.
+    pub fn as_f64(&self) -> f64 {
+        match self.parse() {
+            Some(ParseResult::Double(f)) => f,
+            _ => self.to_decimal_string().parse().unwrap(),
+        }
+    }
+
+    /// The exact value in decimal, like `65535` for `0xFF_FF` or `1500` for `1.5e3`.
+    ///
+    /// Hexadecimal doubles are written as the nearest double instead, and so are exponents
+    /// that don't fit in `i64`. Exponents that would take more than 1024 zeros to expand are
+    /// kept instead, after the digits of the mantissa as an integer: `1.50e-9999` is
+    /// `150e-10001`.
+    pub fn to_decimal_string(&self) -> String {
+        let (sign, magnitude) = match self.lexeme.strip_prefix('-') {
+            Some(magnitude) => ("-", magnitude),
+            None => ("", &self.lexeme[..]),
+        };
+        if magnitude == "NaN" || magnitude == "Infinity" {
+            return self.lexeme.to_string();
+        }
+        let decimal = match magnitude.strip_prefix("0x") {
+            Some(hex) if hex.contains(&['.', 'p', 'P'][..]) => {
+                return self.nearest_double();
+            }
+            Some(hex) => hexadecimal_to_decimal(hex),
+            None => {
+                let magnitude: String = digits(magnitude).collect();
+                match magnitude.find(&['e', 'E'][..]) {
+                    Some(e) => match magnitude[e + 1..].parse() {
+                        Ok(exponent) => shift_point(&magnitude[..e], exponent),
+                        Err(_) => return self.nearest_double(),
+                    },
+                    None if magnitude.starts_with('.') => format!("0{}", magnitude),
+                    None => magnitude,
+                }
+            }
+        };
+        format!("{}{}", sign, decimal)
+    }
+
+    // written as the serializer writes doubles, like `Infinity`
+    fn nearest_double(&self) -> String {
+        ::to_string(&self.as_f64()).unwrap()
+    }
+}
+
+// the most zeros `to_decimal_string` adds to expand an exponent
+#[cfg(feature = "arbitrary_precision")]
+const MAX_ZEROS: i128 = 1024;
+
+// moves the decimal point of `mantissa` by `exponent` digits, keeping all of its digits
+#[cfg(feature = "arbitrary_precision")]
+fn shift_point(mantissa: &str, exponent: i64) -> String {
+    let (integer, fraction) = match mantissa.find('.') {
+        Some(dot) => (&mantissa[..dot], &mantissa[dot + 1..]),
+        None => (mantissa, ""),
+    };
+    let digits = format!("{}{}", integer, fraction);
+    let point = integer.len() as i128 + i128::from(exponent);
+    let zeros = if point <= 0 {
+        -point
+    } else {
+        point - digits.len() as i128
+    };
+    if zeros > MAX_ZEROS {
+        let exponent = i128::from(exponent) - fraction.len() as i128;
+        return match digits.trim_start_matches('0') {
+            "" => "0".to_string(),
+            digits => format!("{}e{}", digits, exponent),
+        };
+    }
+    let (integer, fraction) = if point <= 0 {
+        let zeros = "0".repeat(-point as usize);
+        (String::new(), zeros + &digits)
+    } else if point as usize >= digits.len() {
+        let zeros = "0".repeat(point as usize - digits.len());
+        (digits + &zeros, String::new())
+    } else {
+        let (integer, fraction) = digits.split_at(point as usize);
+        (integer.to_string(), fraction.to_string())
+    };
+    let integer = match integer.trim_start_matches('0') {
+        "" => "0",
+        integer => integer,
+    };
+    if fraction.is_empty() {
+        integer.to_string()
+    } else {
+        format!("{}.{}", integer, fraction)
+    }
+}
+
+// converts in base 10^9 so that any number of digits fits
+#[cfg(feature = "arbitrary_precision")]
+fn hexadecimal_to_decimal(hex: &str) -> String {
+    const BASE: u64 = 1_000_000_000;
+
+    // least significant first
+    let mut limbs = vec![0_u64];
+    for c in digits(hex) {
+        let mut carry = u64::from(c.to_digit(16).unwrap());
+        for limb in &mut limbs {
+            let v = *limb * 16 + carry;
+            *limb = v % BASE;
+            carry = v / BASE;
+        }
+        if carry > 0 {
+            limbs.push(carry);
+        }
+    }
+    let mut decimal = limbs.last().unwrap().to_string();
+    for limb in limbs.iter().rev().skip(1) {
+        decimal.push_str(&format!("{:09}", limb));
+    }
+    decimal
+}
+
+#[cfg(feature = "arbitrary_precision")]
+impl ::std::str::FromStr for Number {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Number> {
+        if literal_len(s)? != s.len() {
+            return Err(Error::TrailingCharacters);
+        }
+        Ok(Number { lexeme: s.into() })
+    }
+}
+
+#[cfg(feature = "arbitrary_precision")]
+impl ::std::fmt::Display for Number {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        f.write_str(&self.lexeme)
+    }
+}
+
+#[cfg(feature = "arbitrary_precision")]
+impl ::serde::Serialize for Number {
+    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+    where
+        S: ::serde::Serializer,
+    {
+        serializer.serialize_newtype_struct(NUMBER_NAME, &*self.lexeme)
+    }
+}
+
+#[cfg(feature = "arbitrary_precision")]
+impl<'de> ::serde::Deserialize<'de> for Number {
+    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+    where
+        D: ::serde::Deserializer<'de>,
+    {
+        use serde::de::{self, MapAccess, Visitor};
+        use std::fmt;
+
+        struct NumberVisitor;
+
+        impl<'de> Visitor<'de> for NumberVisitor {
+            type Value = Number;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a SION number")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> ::std::result::Result<Number, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                match map.next_key::<String>()? {
+                    Some(ref key) if key == NUMBER_FIELD => {
+                        let lexeme: String = map.next_value()?;
+                        Ok(Number {
+                            lexeme: lexeme.into_boxed_str(),
+                        })
+                    }
+                    _ => Err(de::Error::invalid_type(de::Unexpected::Map, &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(NUMBER_NAME, NumberVisitor)
     }
 }
 
@@ -611,7 +1042,7 @@ mod tests {
         let result = parser.run().unwrap();
         assert_eq!(parser.input, "hoge");
         assert_eq!(parser.state, Done);
-        assert_eq!(result, Double(::std::f64::INFINITY));
+        assert_eq!(result, Double(f64::INFINITY));
     }
 
     #[test]
@@ -624,7 +1055,7 @@ mod tests {
         let result = parser.run().unwrap();
         assert_eq!(parser.input, "hoge");
         assert_eq!(parser.state, Done);
-        assert_eq!(result, Double(::std::f64::NEG_INFINITY));
+        assert_eq!(result, Double(f64::NEG_INFINITY));
     }
 
     #[test]
@@ -652,4 +1083,190 @@ mod tests {
         assert_eq!(parser.state, Done);
         assert_eq!(result, Double(1.0));
     }
+
+    #[test]
+    fn test_correctly_rounded() {
+        use super::ParseResult::*;
+        use super::Parser;
+
+        for &(input, expected) in &[
+            ("1.05", 1.05),
+            ("0.1", 0.1),
+            ("0.000_001", 0.000_001),
+            ("1_000.000_1e1_0", 1_000.000_1e10),
+            ("12345678901234567890123.0", 12345678901234567890123.0),
+            ("9007199254740993.0", 9007199254740992.0),
+            ("1.7976931348623157e308", 1.7976931348623157e308),
+            ("1.8e308", f64::INFINITY),
+            ("2.2250738585072014e-308", 2.2250738585072014e-308),
+            ("4.9406564584124654e-324", 5e-324),
+            ("2e-324", 0.0),
+            ("1e5", 100000.0),
+            ("0e0", 0.0),
+        ] {
+            let mut parser = Parser::new(input);
+            assert_eq!(parser.run().unwrap(), Double(expected), "{}", input);
+            assert_eq!(parser.input, "");
+        }
+    }
+
+    #[test]
+    fn test_hex_correctly_rounded() {
+        use super::ParseResult::*;
+        use super::Parser;
+
+        for &(input, expected) in &[
+            ("0x1.8p3", 12.0),
+            ("0xA.8p0", 10.5),
+            ("0x1_0.0_8p-1_0", 0.015655517578125),
+            ("0x0.0001p16", 1.0),
+            ("0x1p-1074", 5e-324),
+            ("0x1p-1075", 0.0),
+            ("0x1.8p-1075", 5e-324),
+            ("0x1.fffffffffffffp1023", f64::MAX),
+            ("0x1p1024", f64::INFINITY),
+            // exactly halfway, round to even
+            ("0x1.00000000000008p0", 1.0),
+            ("0x1.00000000000018p0", 1.0000000000000004),
+            // just above halfway
+            ("0x1.00000000000008000001p0", 1.0000000000000002),
+            ("0x1fffffffffffff8p0", 144115188075855872.0),
+        ] {
+            let mut parser = Parser::new(input);
+            assert_eq!(parser.run().unwrap(), Double(expected), "{}", input);
+            assert_eq!(parser.input, "");
+        }
+    }
+
+    #[test]
+    fn test_negative_zero() {
+        use super::ParseResult::*;
+        use super::Parser;
+
+        let mut parser = Parser::new("-0.5hoge");
+        assert_eq!(parser.run().unwrap(), Double(-0.5));
+        assert_eq!(parser.input, "hoge");
+
+        let mut parser = Parser::new("-0.0");
+        match parser.run().unwrap() {
+            Double(v) => assert!(v == 0.0 && v.is_sign_negative()),
+            v => panic!("{:?}", v),
+        }
+    }
+
+    #[test]
+    fn test_wide_integer() {
+        use super::ParseResult::*;
+        use super::Parser;
+
+        let mut parser = Parser::new("18446744073709551615hoge");
+        assert_eq!(parser.run().unwrap(), UInt(u64::MAX));
+        assert_eq!(parser.input, "hoge");
+
+        let mut parser = Parser::new("-0x8000_0000_0000_0001");
+        assert_eq!(parser.run().unwrap(), Int128(-(1 << 63) - 1));
+
+        let mut parser = Parser::new("0xffff_ffff_ffff_ffff_ffff_ffff_ffff_ffff");
+        assert_eq!(parser.run().unwrap(), UInt128(u128::MAX));
+    }
+
+    #[test]
+    #[cfg(not(feature = "bigint"))]
+    fn test_integer_overflow() {
+        use super::Parser;
+        use Error;
+
+        let mut parser = Parser::new("340282366920938463463374607431768211456");
+        match parser.run() {
+            Err(Error::IntegerOverflow) => {}
+            v => panic!("{:?}", v),
+        }
+    }
+
+    #[cfg(feature = "arbitrary_precision")]
+    #[test]
+    fn test_number() {
+        use super::Number;
+        use value::Value;
+        use {from_str, from_value, to_string, to_value, Error};
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Payment {
+            amount: Number,
+            id: Number,
+        }
+
+        let input = r#"["amount":0.10000000000000000000001,"id":0xFF_FF]"#;
+        let payment = from_str::<Payment>(input).unwrap();
+        assert_eq!(payment.amount.as_str(), "0.10000000000000000000001");
+        assert_eq!(payment.id.as_str(), "0xFF_FF");
+        assert_eq!(to_string(&payment).unwrap(), input);
+
+        assert_eq!(payment.id.as_i64(), Some(65535));
+        assert_eq!(payment.id.as_u64(), Some(65535));
+        assert_eq!(payment.id.to_decimal_string(), "65535");
+        assert_eq!(payment.amount.as_i64(), None);
+        assert_eq!(payment.amount.as_f64(), 0.1);
+        assert_eq!(
+            payment.amount.to_decimal_string(),
+            "0.10000000000000000000001"
+        );
+
+        let huge = "-0x1_0000_0000_0000_0000_0000_0000_0000_0000"
+            .parse::<Number>()
+            .unwrap();
+        assert_eq!(
+            huge.to_decimal_string(),
+            "-340282366920938463463374607431768211456"
+        );
+        assert_eq!(huge.as_i64(), None);
+        assert_eq!(huge.as_f64(), -(2.0_f64.powi(128)));
+        assert_eq!(
+            from_str::<Vec<Number>>("[-.5, 1e3, NaN, 0x1p-1]")
+                .unwrap()
+                .iter()
+                .map(Number::to_decimal_string)
+                .collect::<Vec<_>>(),
+            vec!["-0.5", "1000", "NaN", "0.5"]
+        );
+        let exponents = "[1.5e3, -2.50E+1, 1_2e-4, .5e-1, 0.0e0, 1e99999999999999999999]";
+        assert_eq!(
+            from_str::<Vec<Number>>(exponents)
+                .unwrap()
+                .iter()
+                .map(Number::to_decimal_string)
+                .collect::<Vec<_>>(),
+            vec!["1500", "-25.0", "0.0012", "0.05", "0.0", "Infinity"]
+        );
+        // huge exponents aren't expanded
+        let exponents = "[1e-9000000000000000000, -1.50e1000000000, 0.0e-9223372036854775808, \
+                         1e1024, -1e99999999999999999999, 1e-99999999999999999999]";
+        assert_eq!(
+            from_str::<Vec<Number>>(exponents)
+                .unwrap()
+                .iter()
+                .map(Number::to_decimal_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "1e-9000000000000000000",
+                "-150e999999998",
+                "0",
+                format!("1{}", "0".repeat(1024)).as_str(),
+                "-Infinity",
+                "0.0",
+            ]
+        );
+        assert_eq!("-1".parse::<Number>().unwrap().as_u64(), None);
+
+        match "1 ".parse::<Number>() {
+            Err(Error::TrailingCharacters) => {}
+            v => panic!("{:?}", v),
+        }
+        assert!(from_str::<Number>("\"1\"").is_err());
+
+        assert_eq!(to_value(&payment.id).unwrap(), Value::Int(65535));
+        let number: Number = from_value(Value::Double(1.5)).unwrap();
+        assert_eq!(number.as_str(), "1.5");
+        assert!(from_value::<Number>(Value::Nil).is_err());
+    }
 }
@@ -0,0 +1,172 @@
+use serde::de::{self, Deserialize, Deserializer, MapAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+use std::fmt::{self, Display};
+use Result;
+
+// RawValue is passed through serde as a newtype struct with this name, so that
+// our Serializer/Deserializer can write and capture the text verbatim.
+pub(crate) const NAME: &str = "$__sion_rs_private_RawValue";
+// The deserializer hands the captured text to visitors as a map with this single key.
+pub(crate) const FIELD: &str = "$__sion_rs_private_raw";
+
+/// The source text of a SION value, kept as is.
+///
+/// Deserializing a `RawValue` checks that the input is a valid SION value but doesn't interpret
+/// it, keeping its formatting and the comments inside it. Serializing it writes the text
+/// verbatim.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RawValue {
+    sion: Box<str>,
+}
+
+impl RawValue {
+    /// Fails if `sion` isn't a single SION value.
+    ///
+    /// Only the text of the value is kept: whitespace and comments around it are dropped, so
+    /// that the value can be written inside others.
+    pub fn from_string(sion: String) -> Result<Self> {
+        let raw = ::from_str::<RawValue>(&sion)?;
+        if raw.sion.len() == sion.len() {
+            Ok(RawValue {
+                sion: sion.into_boxed_str(),
+            })
+        } else {
+            Ok(raw)
+        }
+    }
+
+    pub fn get(&self) -> &str {
+        &self.sion
+    }
+}
+
+impl Display for RawValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.sion)
+    }
+}
+
+impl Serialize for RawValue {
+    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(NAME, &*self.sion)
+    }
+}
+
+impl<'de> Deserialize<'de> for RawValue {
+    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct RawValueVisitor;
+
+        impl<'de> Visitor<'de> for RawValueVisitor {
+            type Value = RawValue;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "any valid SION value")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> ::std::result::Result<RawValue, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                match map.next_key::<String>()? {
+                    Some(ref key) if key == FIELD => {
+                        let sion: String = map.next_value()?;
+                        Ok(RawValue {
+                            sion: sion.into_boxed_str(),
+                        })
+                    }
+                    _ => Err(de::Error::invalid_type(de::Unexpected::Map, &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(NAME, RawValueVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_raw_value() {
+        use super::RawValue;
+        use {from_str, to_string};
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Envelope {
+            to: String,
+            payload: RawValue,
+        }
+
+        let input = r#"[
+            "to": "router",
+            "payload": [0xFF: 1.50, // kept
+                        "at": .Date(0.0)]
+        ]"#;
+        let envelope = from_str::<Envelope>(input).unwrap();
+        assert_eq!(envelope.to, "router");
+        assert_eq!(
+            envelope.payload.get(),
+            r#"[0xFF: 1.50, // kept
+                        "at": .Date(0.0)]"#
+        );
+        assert_eq!(
+            to_string(&envelope).unwrap(),
+            r#"["to":"router","payload":[0xFF: 1.50, // kept
+                        "at": .Date(0.0)]]"#
+        );
+
+        let raw = from_str::<Vec<RawValue>>("[1, \"two\" , nil]").unwrap();
+        let raw: Vec<_> = raw.iter().map(RawValue::get).collect();
+        assert_eq!(raw, vec!["1", "\"two\"", "nil"]);
+        assert!(from_str::<Envelope>(r#"["to": "x", "payload": [1, 2]"#).is_err());
+    }
+
+    #[test]
+    fn test_raw_value_conversions() {
+        use super::RawValue;
+        use value::Value;
+        use {from_value, to_value};
+
+        let raw = RawValue::from_string("[\"a\": 0x10]".into()).unwrap();
+        assert_eq!(raw.to_string(), "[\"a\": 0x10]");
+        assert_eq!(to_value(&raw).unwrap(), sion!(["a": 16]));
+        let raw: RawValue = from_value(sion!(["a": [1, nil]])).unwrap();
+        assert_eq!(raw.get(), "[\"a\":[1,nil]]");
+        assert!(RawValue::from_string("[1, 2".into()).is_err());
+        assert!(RawValue::from_string("1 2".into()).is_err());
+        assert_eq!(
+            from_value::<Value>(to_value(&raw).unwrap()).unwrap(),
+            sion!(["a": [1, nil]])
+        );
+    }
+
+    #[test]
+    fn test_raw_value_surroundings() {
+        use super::RawValue;
+        use std::collections::HashMap;
+        use {from_str, to_string};
+
+        let raw = RawValue::from_string(" // before\n1 // after".into()).unwrap();
+        assert_eq!(raw.get(), "1");
+        let raw = RawValue::from_string("[\"a\": 1 // inner\n]\n".into()).unwrap();
+        assert_eq!(raw.get(), "[\"a\": 1 // inner\n]");
+        assert!(RawValue::from_string("1 // c\n2".into()).is_err());
+
+        let one = RawValue::from_string("1 // c".into()).unwrap();
+        let vec = vec![one.clone(), raw.clone()];
+        let sion = to_string(&vec).unwrap();
+        assert_eq!(sion, "[1,[\"a\": 1 // inner\n]]");
+        assert_eq!(from_str::<Vec<RawValue>>(&sion).unwrap(), vec);
+
+        let mut map = HashMap::new();
+        map.insert("one".to_string(), one);
+        let sion = to_string(&map).unwrap();
+        assert_eq!(sion, "[\"one\":1]");
+        assert_eq!(from_str::<HashMap<String, RawValue>>(&sion).unwrap(), map);
+    }
+}
@@ -1,12 +1,165 @@
+use indexmap::IndexMap;
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use value::cmp::StrKey;
 use value::Value;
 
-// FIXME: insertion order
-#[derive(Debug, PartialEq)]
+/// A SION map, which keeps its entries in insertion order.
+///
+/// Inserting an existing key replaces its value but keeps its position, so when a document
+/// has duplicate keys the last value wins. Equality, ordering and hashing don't depend on the
+/// order of entries: maps are compared as if their entries were sorted by key.
+#[derive(Clone, Debug, Default)]
 pub struct Map {
-    pub(crate) contents: Vec<(Value, Value)>,
+    pub(crate) contents: IndexMap<Value, Value>,
 }
 
-#[derive(Debug, PartialEq)]
+impl Map {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Map {
+            contents: IndexMap::with_capacity(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.contents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.contents.is_empty()
+    }
+
+    pub fn get(&self, key: &Value) -> Option<&Value> {
+        self.contents.get(key)
+    }
+
+    pub fn get_mut(&mut self, key: &Value) -> Option<&mut Value> {
+        self.contents.get_mut(key)
+    }
+
+    pub(crate) fn get_str(&self, key: &str) -> Option<&Value> {
+        self.contents.get(&StrKey(key))
+    }
+
+    pub(crate) fn get_str_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.contents.get_mut(&StrKey(key))
+    }
+
+    pub fn get_index(&self, index: usize) -> Option<(&Value, &Value)> {
+        self.contents.get_index(index)
+    }
+
+    pub fn contains_key(&self, key: &Value) -> bool {
+        self.contents.contains_key(key)
+    }
+
+    /// Sets the value of `key`, returning the previous one. A new key goes last.
+    pub fn insert(&mut self, key: Value, value: Value) -> Option<Value> {
+        self.contents.insert(key, value)
+    }
+
+    /// Removes `key`, keeping the order of the other entries.
+    pub fn remove(&mut self, key: &Value) -> Option<Value> {
+        self.contents.shift_remove(key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Value, &Value)> {
+        self.contents.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&Value, &mut Value)> {
+        self.contents.iter_mut()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &Value> {
+        self.contents.keys()
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        self.contents.values()
+    }
+
+    fn sorted(&self) -> Vec<(&Value, &Value)> {
+        let mut entries: Vec<_> = self.contents.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        entries
+    }
+}
+
+impl PartialEq for Map {
+    fn eq(&self, other: &Map) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl Eq for Map {}
+
+impl PartialOrd for Map {
+    fn partial_cmp(&self, other: &Map) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// sorts references to the entries of both maps on each call, which takes O(n log n) time
+impl Ord for Map {
+    fn cmp(&self, other: &Map) -> Ordering {
+        self.sorted().cmp(&other.sorted())
+    }
+}
+
+// the entries are hashed one by one and their hashes summed, which doesn't depend on the order
+impl Hash for Map {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.contents.len().hash(state);
+        let sum = self.contents.iter().fold(0_u64, |sum, entry| {
+            let mut hasher = DefaultHasher::new();
+            entry.hash(&mut hasher);
+            sum.wrapping_add(hasher.finish())
+        });
+        sum.hash(state);
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Array {
     pub(crate) contents: Vec<Value>,
 }
+
+impl Array {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.contents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.contents.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Value> {
+        self.contents.get(index)
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut Value> {
+        self.contents.get_mut(index)
+    }
+
+    pub fn push(&mut self, value: Value) {
+        self.contents.push(value)
+    }
+
+    pub fn iter(&self) -> ::std::slice::Iter<'_, Value> {
+        self.contents.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> ::std::slice::IterMut<'_, Value> {
+        self.contents.iter_mut()
+    }
+}
@@ -1,35 +1,197 @@
 use super::{Error, Result};
 use serde::ser::{self, Serialize};
+use std::io;
+
+pub struct Serializer<W> {
+    writer: W,
+    // write the next string verbatim
+    raw: bool,
+    // one entry per line, indented by the nesting depth
+    pretty: bool,
+    depth: usize,
+}
+
+impl<W> Serializer<W>
+where
+    W: io::Write,
+{
+    pub fn new(writer: W) -> Self {
+        Serializer {
+            writer,
+            raw: false,
+            pretty: false,
+            depth: 0,
+        }
+    }
+
+    /// A serializer that puts each element on its own line, indented by four spaces.
+    pub fn pretty(writer: W) -> Self {
+        Serializer {
+            pretty: true,
+            ..Serializer::new(writer)
+        }
+    }
 
-pub struct Serializer {
-    output: String,
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn open(&mut self) -> io::Result<()> {
+        self.depth += 1;
+        self.writer.write_all(b"[")
+    }
+
+    fn close(&mut self, empty: bool) -> io::Result<()> {
+        self.depth -= 1;
+        if !empty {
+            self.newline()?;
+        }
+        self.writer.write_all(b"]")
+    }
+
+    fn newline(&mut self) -> io::Result<()> {
+        if self.pretty {
+            self.writer.write_all(b"\n")?;
+            for _ in 0..self.depth {
+                self.writer.write_all(b"    ")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn serialize_raw<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.raw = true;
+        let result = value.serialize(&mut *self);
+        self.raw = false;
+        result
+    }
+
+    fn colon(&mut self) -> io::Result<()> {
+        self.writer
+            .write_all(if self.pretty { b": " } else { b":" })
+    }
+}
+
+pub fn to_writer<W, T>(writer: W, value: &T) -> Result<()>
+where
+    W: io::Write,
+    T: Serialize + ?Sized,
+{
+    let mut serializer = Serializer::new(writer);
+    value.serialize(&mut serializer)
+}
+
+pub fn to_vec<T>(value: &T) -> Result<Vec<u8>>
+where
+    T: Serialize + ?Sized,
+{
+    let mut output = Vec::with_capacity(128);
+    to_writer(&mut output, value)?;
+    Ok(output)
 }
 
 pub fn to_string<T>(value: &T) -> Result<String>
 where
     T: Serialize + ?Sized,
 {
-    let mut serializer = Serializer {
-        output: String::new(),
-    };
-    value.serialize(&mut serializer)?;
-    Ok(serializer.output)
+    let output = to_vec(value)?;
+    Ok(String::from_utf8(output).expect("the serializer only writes UTF-8"))
+}
+
+pub fn to_writer_pretty<W, T>(writer: W, value: &T) -> Result<()>
+where
+    W: io::Write,
+    T: Serialize + ?Sized,
+{
+    let mut serializer = Serializer::pretty(writer);
+    value.serialize(&mut serializer)
+}
+
+pub fn to_vec_pretty<T>(value: &T) -> Result<Vec<u8>>
+where
+    T: Serialize + ?Sized,
+{
+    let mut output = Vec::with_capacity(128);
+    to_writer_pretty(&mut output, value)?;
+    Ok(output)
 }
 
-impl<'a> ser::Serializer for &'a mut Serializer {
+pub fn to_string_pretty<T>(value: &T) -> Result<String>
+where
+    T: Serialize + ?Sized,
+{
+    let output = to_vec_pretty(value)?;
+    Ok(String::from_utf8(output).expect("the serializer only writes UTF-8"))
+}
+
+// `repr` is the Debug representation of a float, which is the shortest one that round-trips.
+// It always has a fraction unless it has an exponent, in which case we add one
+// so that integral values stay doubles.
+fn write_double<W>(writer: &mut W, repr: &str) -> io::Result<()>
+where
+    W: io::Write,
+{
+    match repr {
+        "NaN" => writer.write_all(b"NaN"),
+        "inf" => writer.write_all(b"Infinity"),
+        "-inf" => writer.write_all(b"-Infinity"),
+        _ => match repr.find('e') {
+            Some(e) if !repr[..e].contains('.') => {
+                writer.write_all(&repr.as_bytes()[..e])?;
+                writer.write_all(b".0")?;
+                writer.write_all(&repr.as_bytes()[e..])
+            }
+            _ => writer.write_all(repr.as_bytes()),
+        },
+    }
+}
+
+fn escape_str<W>(writer: &mut W, v: &str) -> io::Result<()>
+where
+    W: io::Write,
+{
+    let mut start = 0;
+    for (i, c) in v.char_indices() {
+        let escaped: Option<&[u8]> = match c {
+            '"' => Some(b"\\\""),
+            '\\' => Some(b"\\\\"),
+            '\n' => Some(b"\\n"),
+            '\r' => Some(b"\\r"),
+            '\t' => Some(b"\\t"),
+            '\0' => Some(b"\\0"),
+            c if c.is_control() => None,
+            _ => continue,
+        };
+        writer.write_all(&v.as_bytes()[start..i])?;
+        match escaped {
+            Some(escaped) => writer.write_all(escaped)?,
+            None => write!(writer, "\\u{{{:x}}}", c as u32)?,
+        }
+        start = i + c.len_utf8();
+    }
+    writer.write_all(&v.as_bytes()[start..])
+}
+
+impl<'a, W> ser::Serializer for &'a mut Serializer<W>
+where
+    W: io::Write,
+{
     type Ok = ();
     type Error = Error;
 
-    type SerializeSeq = Self;
-    type SerializeTuple = Self;
-    type SerializeTupleStruct = Self;
-    type SerializeTupleVariant = Self;
-    type SerializeMap = Self;
-    type SerializeStruct = Self;
-    type SerializeStructVariant = Self;
+    type SerializeSeq = Compound<'a, W>;
+    type SerializeTuple = Compound<'a, W>;
+    type SerializeTupleStruct = Compound<'a, W>;
+    type SerializeTupleVariant = Compound<'a, W>;
+    type SerializeMap = Compound<'a, W>;
+    type SerializeStruct = Compound<'a, W>;
+    type SerializeStructVariant = Compound<'a, W>;
 
     fn serialize_bool(self, v: bool) -> Result<()> {
-        self.output += if v { "true" } else { "false" };
+        self.writer.write_all(if v { b"true" } else { b"false" })?;
         Ok(())
     }
 
@@ -43,7 +205,11 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         self.serialize_i64(i64::from(v))
     }
     fn serialize_i64(self, v: i64) -> Result<()> {
-        self.output += &v.to_string();
+        write!(self.writer, "{}", v)?;
+        Ok(())
+    }
+    fn serialize_i128(self, v: i128) -> Result<()> {
+        write!(self.writer, "{}", v)?;
         Ok(())
     }
     fn serialize_u8(self, v: u8) -> Result<()> {
@@ -56,34 +222,42 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         self.serialize_i64(i64::from(v))
     }
     fn serialize_u64(self, v: u64) -> Result<()> {
-        self.serialize_i64(v as i64)
+        write!(self.writer, "{}", v)?;
+        Ok(())
+    }
+    fn serialize_u128(self, v: u128) -> Result<()> {
+        write!(self.writer, "{}", v)?;
+        Ok(())
     }
 
     fn serialize_f32(self, v: f32) -> Result<()> {
-        self.serialize_f64(f64::from(v))
+        write_double(&mut self.writer, &format!("{:?}", v))?;
+        Ok(())
     }
     fn serialize_f64(self, v: f64) -> Result<()> {
-        self.output += &v.to_string();
+        write_double(&mut self.writer, &format!("{:?}", v))?;
         Ok(())
     }
 
     fn serialize_char(self, v: char) -> Result<()> {
-        self.output += &v.to_string();
-        Ok(())
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
     }
 
-    // TODO: handle '"' in string
     fn serialize_str(self, v: &str) -> Result<()> {
-        self.output += "\"";
-        self.output += v;
-        self.output += "\"";
+        if self.raw {
+            self.writer.write_all(v.as_bytes())?;
+            return Ok(());
+        }
+        self.writer.write_all(b"\"")?;
+        escape_str(&mut self.writer, v)?;
+        self.writer.write_all(b"\"")?;
         Ok(())
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<()> {
-        self.output += ".Data(\"";
-        self.output += &::base64::encode(v);
-        self.output += "\")";
+        self.writer.write_all(b".Data(\"")?;
+        self.writer.write_all(::base64::encode(v).as_bytes())?;
+        self.writer.write_all(b"\")")?;
         Ok(())
     }
 
@@ -99,7 +273,7 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     }
 
     fn serialize_unit(self) -> Result<()> {
-        self.output += "nil";
+        self.writer.write_all(b"nil")?;
         Ok(())
     }
 
@@ -116,11 +290,35 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         self.serialize_str(variant)
     }
 
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<()>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<()>
     where
         T: Serialize + ?Sized,
     {
-        value.serialize(self)
+        // the digits of an integer beyond 128 bits, written without quotes
+        #[cfg(feature = "bigint")]
+        {
+            if name == ::number::BIGINT_NAME {
+                return self.serialize_raw(value);
+            }
+        }
+        // a number literal, written as is
+        #[cfg(feature = "arbitrary_precision")]
+        {
+            if name == ::number::NUMBER_NAME {
+                return self.serialize_raw(value);
+            }
+        }
+        if name == ::date::NAME {
+            self.writer.write_all(b".Date(")?;
+            value.serialize(&mut *self)?;
+            self.writer.write_all(b")")?;
+            Ok(())
+        } else if name == ::raw::NAME {
+            // the text of a `RawValue`, written without quotes
+            self.serialize_raw(value)
+        } else {
+            value.serialize(self)
+        }
     }
 
     fn serialize_newtype_variant<T>(
@@ -133,24 +331,25 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     where
         T: Serialize + ?Sized,
     {
-        self.output += "[";
+        self.open()?;
+        self.newline()?;
         variant.serialize(&mut *self)?;
-        self.output += ":";
+        self.colon()?;
         value.serialize(&mut *self)?;
-        self.output += "]";
+        self.close(false)?;
         Ok(())
     }
 
-    fn serialize_seq(self, _len: Option<usize>) -> Result<Self> {
-        self.output += "[";
-        Ok(self)
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'a, W>> {
+        self.open()?;
+        Ok(Compound::new(self))
     }
 
-    fn serialize_tuple(self, len: usize) -> Result<Self> {
+    fn serialize_tuple(self, len: usize) -> Result<Compound<'a, W>> {
         self.serialize_seq(Some(len))
     }
 
-    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<Self> {
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<Compound<'a, W>> {
         self.serialize_seq(Some(len))
     }
 
@@ -160,19 +359,21 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         _variant_index: u32,
         variant: &'static str,
         _len: usize,
-    ) -> Result<Self> {
-        self.output += "[";
+    ) -> Result<Compound<'a, W>> {
+        self.open()?;
+        self.newline()?;
         variant.serialize(&mut *self)?;
-        self.output += ":";
-        Ok(self)
+        self.colon()?;
+        self.open()?;
+        Ok(Compound::new(self))
     }
 
-    fn serialize_map(self, _len: Option<usize>) -> Result<Self> {
-        self.output += "[";
-        Ok(self)
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'a, W>> {
+        self.open()?;
+        Ok(Compound::new(self))
     }
 
-    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self> {
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Compound<'a, W>> {
         self.serialize_map(Some(len))
     }
 
@@ -182,15 +383,63 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         _variant_index: u32,
         variant: &'static str,
         _len: usize,
-    ) -> Result<Self> {
-        self.output += "[";
+    ) -> Result<Compound<'a, W>> {
+        self.open()?;
+        self.newline()?;
         variant.serialize(&mut *self)?;
-        self.output += ":[";
-        Ok(self)
+        self.colon()?;
+        self.open()?;
+        Ok(Compound::new(self))
     }
 }
 
-impl<'a> ser::SerializeSeq for &'a mut Serializer {
+// state of an array or a map being serialized
+pub struct Compound<'a, W: 'a> {
+    serializer: &'a mut Serializer<W>,
+    first: bool,
+}
+
+impl<'a, W> Compound<'a, W>
+where
+    W: io::Write,
+{
+    fn new(serializer: &'a mut Serializer<W>) -> Self {
+        Compound {
+            serializer,
+            first: true,
+        }
+    }
+
+    fn separate(&mut self) -> Result<()> {
+        if !self.first {
+            self.serializer.writer.write_all(b",")?;
+        }
+        self.first = false;
+        self.serializer.newline()?;
+        Ok(())
+    }
+
+    // `variant` closes the map around the contents of an enum variant as well
+    fn end_array(self, variant: bool) -> Result<()> {
+        self.serializer.close(self.first)?;
+        if variant {
+            self.serializer.close(false)?;
+        }
+        Ok(())
+    }
+
+    fn end_map(self, variant: bool) -> Result<()> {
+        if self.first {
+            self.serializer.writer.write_all(b":")?;
+        }
+        self.end_array(variant)
+    }
+}
+
+impl<'a, W> ser::SerializeSeq for Compound<'a, W>
+where
+    W: io::Write,
+{
     type Ok = ();
     type Error = Error;
 
@@ -198,19 +447,19 @@ impl<'a> ser::SerializeSeq for &'a mut Serializer {
     where
         T: Serialize + ?Sized,
     {
-        if !self.output.ends_with("[") {
-            self.output += ",";
-        }
-        value.serialize(&mut **self)
+        self.separate()?;
+        value.serialize(&mut *self.serializer)
     }
 
     fn end(self) -> Result<()> {
-        self.output += "]";
-        Ok(())
+        self.end_array(false)
     }
 }
 
-impl<'a> ser::SerializeTuple for &'a mut Serializer {
+impl<'a, W> ser::SerializeTuple for Compound<'a, W>
+where
+    W: io::Write,
+{
     type Ok = ();
     type Error = Error;
 
@@ -218,19 +467,19 @@ impl<'a> ser::SerializeTuple for &'a mut Serializer {
     where
         T: Serialize + ?Sized,
     {
-        if !self.output.ends_with("[") {
-            self.output += ",";
-        }
-        value.serialize(&mut **self)
+        self.separate()?;
+        value.serialize(&mut *self.serializer)
     }
 
     fn end(self) -> Result<()> {
-        self.output += "]";
-        Ok(())
+        self.end_array(false)
     }
 }
 
-impl<'a> ser::SerializeTupleStruct for &'a mut Serializer {
+impl<'a, W> ser::SerializeTupleStruct for Compound<'a, W>
+where
+    W: io::Write,
+{
     type Ok = ();
     type Error = Error;
 
@@ -238,19 +487,19 @@ impl<'a> ser::SerializeTupleStruct for &'a mut Serializer {
     where
         T: Serialize + ?Sized,
     {
-        if !self.output.ends_with("[") {
-            self.output += ",";
-        }
-        value.serialize(&mut **self)
+        self.separate()?;
+        value.serialize(&mut *self.serializer)
     }
 
     fn end(self) -> Result<()> {
-        self.output += "]]";
-        Ok(())
+        self.end_array(false)
     }
 }
 
-impl<'a> ser::SerializeTupleVariant for &'a mut Serializer {
+impl<'a, W> ser::SerializeTupleVariant for Compound<'a, W>
+where
+    W: io::Write,
+{
     type Ok = ();
     type Error = Error;
 
@@ -258,19 +507,19 @@ impl<'a> ser::SerializeTupleVariant for &'a mut Serializer {
     where
         T: Serialize + ?Sized,
     {
-        if !self.output.ends_with("[") {
-            self.output += ",";
-        }
-        value.serialize(&mut **self)
+        self.separate()?;
+        value.serialize(&mut *self.serializer)
     }
 
     fn end(self) -> Result<()> {
-        self.output += "]";
-        Ok(())
+        self.end_array(true)
     }
 }
 
-impl<'a> ser::SerializeMap for &'a mut Serializer {
+impl<'a, W> ser::SerializeMap for Compound<'a, W>
+where
+    W: io::Write,
+{
     type Ok = ();
     type Error = Error;
 
@@ -278,30 +527,27 @@ impl<'a> ser::SerializeMap for &'a mut Serializer {
     where
         T: Serialize + ?Sized,
     {
-        if !self.output.ends_with("[") {
-            self.output += ",";
-        }
-        key.serialize(&mut **self)
+        self.separate()?;
+        key.serialize(&mut *self.serializer)
     }
 
     fn serialize_value<T>(&mut self, value: &T) -> Result<()>
     where
         T: Serialize + ?Sized,
     {
-        self.output += ":";
-        value.serialize(&mut **self)
+        self.serializer.colon()?;
+        value.serialize(&mut *self.serializer)
     }
 
     fn end(self) -> Result<()> {
-        if self.output.ends_with("[") {
-            self.output += ":";
-        }
-        self.output += "]";
-        Ok(())
+        self.end_map(false)
     }
 }
 
-impl<'a> ser::SerializeStruct for &'a mut Serializer {
+impl<'a, W> ser::SerializeStruct for Compound<'a, W>
+where
+    W: io::Write,
+{
     type Ok = ();
     type Error = Error;
 
@@ -309,24 +555,21 @@ impl<'a> ser::SerializeStruct for &'a mut Serializer {
     where
         T: Serialize + ?Sized,
     {
-        if !self.output.ends_with("[") {
-            self.output += ",";
-        }
-        key.serialize(&mut **self)?;
-        self.output += ":";
-        value.serialize(&mut **self)
+        self.separate()?;
+        key.serialize(&mut *self.serializer)?;
+        self.serializer.colon()?;
+        value.serialize(&mut *self.serializer)
     }
 
     fn end(self) -> Result<()> {
-        if self.output.ends_with("[") {
-            self.output += ":";
-        }
-        self.output += "]";
-        Ok(())
+        self.end_map(false)
     }
 }
 
-impl<'a> ser::SerializeStructVariant for &'a mut Serializer {
+impl<'a, W> ser::SerializeStructVariant for Compound<'a, W>
+where
+    W: io::Write,
+{
     type Ok = ();
     type Error = Error;
 
@@ -334,19 +577,214 @@ impl<'a> ser::SerializeStructVariant for &'a mut Serializer {
     where
         T: Serialize + ?Sized,
     {
-        if !self.output.ends_with("[") {
-            self.output += ",";
-        }
-        key.serialize(&mut **self)?;
-        self.output += ":";
-        value.serialize(&mut **self)
+        self.separate()?;
+        key.serialize(&mut *self.serializer)?;
+        self.serializer.colon()?;
+        value.serialize(&mut *self.serializer)
     }
 
     fn end(self) -> Result<()> {
-        if self.output.ends_with("[") {
-            self.output += ":";
+        self.end_map(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_writer() {
+        use super::Serializer;
+        use serde::Serialize;
+        use std::collections::BTreeMap;
+        use {to_vec, to_writer};
+
+        let mut map = BTreeMap::new();
+        map.insert("empty", vec![]);
+        map.insert("one", vec![1]);
+        assert_eq!(to_vec(&map).unwrap(), br#"["empty":[],"one":[1]]"#.to_vec());
+
+        let mut output = vec![];
+        to_writer(&mut output, &BTreeMap::<(), ()>::new()).unwrap();
+        to_writer(&mut output, &()).unwrap();
+        assert_eq!(output, b"[:]nil".to_vec());
+
+        let mut serializer = Serializer::new(vec![]);
+        "dankogai".serialize(&mut serializer).unwrap();
+        48.serialize(&mut serializer).unwrap();
+        assert_eq!(serializer.into_inner(), br#""dankogai"48"#.to_vec());
+    }
+
+    #[test]
+    fn test_writer_error() {
+        use std::io::{self, Write};
+        use {to_writer, Error};
+
+        struct Full;
+
+        impl Write for Full {
+            fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+                Err(io::Error::other("disk full"))
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
         }
-        self.output += "]]";
-        Ok(())
+
+        match to_writer(Full, &[1, 2, 3]) {
+            Err(Error::Io(ref e)) if e.to_string() == "disk full" => {}
+            v => panic!("{:?}", v),
+        }
+    }
+
+    #[test]
+    fn test_double() {
+        use to_string;
+
+        assert_eq!(to_string(&1.0).unwrap(), "1.0");
+        assert_eq!(to_string(&-0.0).unwrap(), "-0.0");
+        assert_eq!(to_string(&0.1).unwrap(), "0.1");
+        assert_eq!(to_string(&0.1_f32).unwrap(), "0.1");
+        assert_eq!(to_string(&16_777_216_f32).unwrap(), "16777216.0");
+        assert_eq!(to_string(&1e300).unwrap(), "1.0e300");
+        assert_eq!(to_string(&-1.5e-7).unwrap(), "-1.5e-7");
+        assert_eq!(to_string(&5e-324).unwrap(), "5.0e-324");
+        assert_eq!(to_string(&f64::NAN).unwrap(), "NaN");
+        assert_eq!(to_string(&f32::INFINITY).unwrap(), "Infinity");
+        assert_eq!(to_string(&f64::NEG_INFINITY).unwrap(), "-Infinity");
+    }
+
+    #[test]
+    fn test_double_round_trip() {
+        use value::Value;
+        use {from_str, to_string};
+
+        for &v in &[
+            0.0,
+            1.0,
+            -42.0,
+            0.1,
+            1.0 / 3.0,
+            1e16,
+            1e22,
+            123456789.0e-30,
+            f64::MAX,
+            f64::MIN_POSITIVE,
+            5e-324,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+        ] {
+            let serialized = to_string(&v).unwrap();
+            assert_eq!(from_str::<f64>(&serialized).unwrap(), v);
+            assert_eq!(from_str::<Value>(&serialized).unwrap(), Value::Double(v));
+        }
+        for &v in &[0.1_f32, 1.0 / 3.0, f32::MAX, f32::MIN_POSITIVE, 1e-45] {
+            let serialized = to_string(&v).unwrap();
+            assert_eq!(from_str::<f32>(&serialized).unwrap(), v);
+        }
+        let nan = to_string(&f64::NAN).unwrap();
+        assert!(from_str::<f64>(&nan).unwrap().is_nan());
+    }
+
+    #[test]
+    fn test_escape() {
+        use to_string;
+
+        assert_eq!(to_string("dankogai").unwrap(), r#""dankogai""#);
+        assert_eq!(
+            to_string("say \"hello\"\\n").unwrap(),
+            r#""say \"hello\"\\n""#
+        );
+        assert_eq!(to_string("\0\t\n\r漢字😇").unwrap(), r#""\0\t\n\r漢字😇""#);
+        assert_eq!(
+            to_string("\u{1}\u{1b}[0m\u{7f}").unwrap(),
+            r#""\u{1}\u{1b}[0m\u{7f}""#
+        );
+        assert_eq!(to_string(&'"').unwrap(), r#""\"""#);
+    }
+
+    #[test]
+    fn test_escape_round_trip() {
+        use {from_str, to_string};
+
+        for s in &[
+            "",
+            "\"",
+            "\\",
+            "\\\"",
+            "C:\\Program Files\\",
+            "line 1\nline 2\r\nline 3",
+            "tab\tseparated\0",
+            "[\"not\": \"a map\"]",
+            "// not a comment",
+            "漢字、カタカナ、ひらがなの入ったstring😇",
+            "\u{1}\u{1b}[0m\u{7f}\u{85}\u{9f}",
+        ] {
+            let serialized = to_string(s).unwrap();
+            assert_eq!(&from_str::<String>(&serialized).unwrap(), s);
+        }
+    }
+
+    #[test]
+    fn test_pretty() {
+        use std::collections::BTreeMap;
+        use value::Value;
+        use {from_str, to_string_pretty};
+
+        #[derive(Serialize)]
+        enum Shape {
+            Point,
+            Circle(f64),
+            Line(i32, i32),
+            Rect { w: i32, h: i32 },
+        }
+
+        let mut map = BTreeMap::new();
+        map.insert("empty", vec![]);
+        map.insert("one", vec![1]);
+        map.insert("two", vec![1, 2]);
+        let expected = r#"[
+    "empty": [],
+    "one": [
+        1
+    ],
+    "two": [
+        1,
+        2
+    ]
+]"#;
+        assert_eq!(to_string_pretty(&map).unwrap(), expected);
+        assert_eq!(to_string_pretty(&BTreeMap::<(), ()>::new()).unwrap(), "[:]");
+        assert_eq!(to_string_pretty(&1).unwrap(), "1");
+
+        let shapes = vec![
+            Shape::Point,
+            Shape::Circle(1.0),
+            Shape::Line(1, 2),
+            Shape::Rect { w: 3, h: 4 },
+        ];
+        let expected = r#"[
+    "Point",
+    [
+        "Circle": 1.0
+    ],
+    [
+        "Line": [
+            1,
+            2
+        ]
+    ],
+    [
+        "Rect": [
+            "w": 3,
+            "h": 4
+        ]
+    ]
+]"#;
+        let pretty = to_string_pretty(&shapes).unwrap();
+        assert_eq!(pretty, expected);
+        assert_eq!(
+            from_str::<Value>(&pretty).unwrap(),
+            from_str::<Value>(&::to_string(&shapes).unwrap()).unwrap()
+        );
     }
 }
@@ -3,61 +3,75 @@ use std::borrow::Cow;
 
 type ParseResult<'a, T> = Result<(T, &'a str)>;
 
-fn expect<'a, 'b>(input: &'a str, expected: &'b str) -> ParseResult<'a, ()> {
-    if input.starts_with(expected) {
-        Ok(((), &input[expected.len()..]))
-    } else {
-        Err(self::Error::Expected(expected.into()))
+fn expect<'a>(input: &'a str, expected: &str) -> ParseResult<'a, ()> {
+    match input.strip_prefix(expected) {
+        Some(output) => Ok(((), output)),
+        None if expected.starts_with(input) => Err(self::Error::Eof),
+        None => Err(self::Error::Expected(expected.into())),
     }
 }
 
-fn parse_unicode_hex<I>(input: &mut I) -> Result<(char, usize)>
-where
-    I: Iterator<Item = char>,
-{
-    use try_from::TryInto;
+// parse `{XXXX}` with 1 to 8 hexadecimal digits, returning the value and the fed bytes
+fn parse_unicode_hex(input: &str) -> Result<(u32, usize)> {
+    let input = match input.chars().next() {
+        Some('{') => &input[1..],
+        Some(_) => return Err(self::Error::Expected("{".into())),
+        None => return Err(self::Error::Eof),
+    };
 
     let mut ret = 0_u32;
-    let mut fed_bytes = 0;
-    for _ in 0..8 {
-        ret <<= 8;
-        if let Some(c) = input.next() {
-            match c {
-                '0' => ret += 0,
-                '1' => ret += 1,
-                '2' => ret += 2,
-                '3' => ret += 3,
-                '4' => ret += 4,
-                '5' => ret += 5,
-                '6' => ret += 6,
-                '7' => ret += 7,
-                '8' => ret += 8,
-                '9' => ret += 9,
-                'a' | 'B' => ret += 10,
-                'b' | 'V' => ret += 11,
-                'c' | 'C' => ret += 12,
-                'd' | 'D' => ret += 13,
-                'e' | 'E' => ret += 14,
-                'f' | 'F' => ret += 15,
-                _ => {
-                    return ret
-                        .try_into()
-                        .map(|c| (c, fed_bytes))
-                        .map_err(|_| self::Error::UnicodeConversionError(ret))
-                }
+    let mut digits = 0;
+    for c in input.chars() {
+        match c.to_digit(16) {
+            Some(d) if digits < 8 => {
+                ret = ret << 4 | d;
+                digits += 1;
             }
-            fed_bytes += c.len_utf8();
-        } else {
-            break;
+            None if c == '}' && digits > 0 => {
+                return Ok((ret, '{'.len_utf8() + digits + '}'.len_utf8()));
+            }
+            None if digits == 0 => return Err(self::Error::ExpectedHexadecimalDigit),
+            _ => return Err(self::Error::Expected("}".into())),
         }
     }
 
-    ret.try_into()
+    Err(self::Error::Eof)
+}
+
+// parse the rest of `\u{XXXX}`, combining a surrogate pair written as two escapes
+fn parse_unicode_escape(input: &str) -> Result<(char, usize)> {
+    use try_from::TryInto;
+
+    let (high, mut fed_bytes) = parse_unicode_hex(input)?;
+    let scalar = match high {
+        0xD800..=0xDBFF => {
+            let rest = &input[fed_bytes..];
+            if !rest.starts_with("\\u") {
+                return if "\\u".starts_with(rest) {
+                    Err(self::Error::Eof)
+                } else {
+                    Err(self::Error::LoneSurrogate(high))
+                };
+            }
+            let (low, fed) = parse_unicode_hex(&rest["\\u".len()..])?;
+            if let 0xDC00..=0xDFFF = low {
+                fed_bytes += "\\u".len() + fed;
+                0x10000 + ((high - 0xD800) << 10 | (low - 0xDC00))
+            } else {
+                return Err(self::Error::LoneSurrogate(high));
+            }
+        }
+        0xDC00..=0xDFFF => return Err(self::Error::LoneSurrogate(high)),
+        _ => high,
+    };
+
+    scalar
+        .try_into()
         .map(|c| (c, fed_bytes))
-        .map_err(|_| self::Error::UnicodeConversionError(ret))
+        .map_err(|_| self::Error::UnicodeConversionError(scalar))
 }
 
-pub(crate) fn parse_string_literal(input: &str) -> ParseResult<Cow<str>> {
+pub(crate) fn parse_string_literal(input: &str) -> ParseResult<'_, Cow<'_, str>> {
     use self::Cow::*;
 
     let ((), input) = expect(input, "\"")?;
@@ -110,7 +124,7 @@ pub(crate) fn parse_string_literal(input: &str) -> ParseResult<Cow<str>> {
             }
             '\'' if escape_next => {
                 if let Owned(ref mut s) = ret {
-                    s.push('\\');
+                    s.push('\'');
                     escape_next = false;
                 } else {
                     unreachable!()
@@ -125,7 +139,9 @@ pub(crate) fn parse_string_literal(input: &str) -> ParseResult<Cow<str>> {
                 }
             }
             'u' if escape_next => {
-                let (c, fed) = parse_unicode_hex(&mut chars)?;
+                let start = fed_bytes + 'u'.len_utf8();
+                let (c, fed) = parse_unicode_escape(&input[start..])?;
+                chars = input[(start + fed)..].chars();
                 fed_bytes += fed;
                 if let Owned(ref mut s) = ret {
                     s.push(c);
@@ -144,6 +160,7 @@ pub(crate) fn parse_string_literal(input: &str) -> ParseResult<Cow<str>> {
                     return Ok((ret, &input[(fed_bytes + '"'.len_utf8())..]));
                 }
             }
+            _ if escape_next => return Err(self::Error::UnexpectedEscape(c)),
             '\r' | '\n' => return Err(self::Error::UnexpectedLineBreak(c)),
             '\\' => {
                 if let Borrowed(_) = ret {
@@ -193,4 +210,78 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn test_invalid_escape() {
+        use super::parse_string_literal;
+        use super::Cow::*;
+        use Error;
+
+        assert_eq!(
+            parse_string_literal(r#""it\'s""#).unwrap(),
+            (Owned("it's".into()), "")
+        );
+        match parse_string_literal(r#""\qn""#) {
+            Err(Error::UnexpectedEscape('q')) => {}
+            v => panic!("{:?}", v),
+        }
+    }
+
+    #[test]
+    fn test_unicode_escape() {
+        use super::parse_string_literal;
+
+        for &(input, expected) in &[
+            (r#""\u{41}B""#, "AB"),
+            (r#""\u{0}\u{7f}""#, "\0\u{7f}"),
+            (r#""\u{00000041}""#, "A"),
+            (r#""\u{6f22}\u{5B57}""#, "漢字"),
+            (r#""\u{1F607}""#, "😇"),
+            (r#""\u{D83D}\u{DE07}!""#, "😇!"),
+            (r#""\u{10FFFF}""#, "\u{10FFFF}"),
+        ] {
+            let (s, output) = parse_string_literal(input).unwrap();
+            assert_eq!(s, expected);
+            assert_eq!(output, "");
+        }
+    }
+
+    #[test]
+    fn test_invalid_unicode_escape() {
+        use super::parse_string_literal;
+        use Error;
+
+        match parse_string_literal(r#""\u{D83D}""#) {
+            Err(Error::LoneSurrogate(0xD83D)) => {}
+            v => panic!("{:?}", v),
+        }
+        match parse_string_literal(r#""\u{D83D}\u{41}""#) {
+            Err(Error::LoneSurrogate(0xD83D)) => {}
+            v => panic!("{:?}", v),
+        }
+        match parse_string_literal(r#""\u{DE07}""#) {
+            Err(Error::LoneSurrogate(0xDE07)) => {}
+            v => panic!("{:?}", v),
+        }
+        match parse_string_literal(r#""\u{110000}""#) {
+            Err(Error::UnicodeConversionError(0x110000)) => {}
+            v => panic!("{:?}", v),
+        }
+        match parse_string_literal(r#""\u{}""#) {
+            Err(Error::ExpectedHexadecimalDigit) => {}
+            v => panic!("{:?}", v),
+        }
+        for input in &[r#""\u41""#, r#""\u{000000041}""#, r#""\u{4g}""#] {
+            match parse_string_literal(input) {
+                Err(Error::Expected(_)) => {}
+                v => panic!("{:?}", v),
+            }
+        }
+        for input in &[r#""\u"#, r#""\u{4"#, r#""\u{D83D}\"#] {
+            match parse_string_literal(input) {
+                Err(Error::Eof) => {}
+                v => panic!("{:?}", v),
+            }
+        }
+    }
 }
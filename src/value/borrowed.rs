@@ -0,0 +1,233 @@
+use super::de::integer;
+use super::Value;
+use number::ParseResult;
+use sequence::{Array, Map};
+#[cfg(feature = "bigint")]
+use serde::de;
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use std::borrow::Cow;
+use std::fmt;
+
+/// A SION value that borrows strings from the input where possible.
+///
+/// Deserialize it with `from_str` or `from_slice`: strings and keys without escape sequences
+/// point into the input instead of being copied. Maps keep all of their entries in the order
+/// of the input, including duplicate keys.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValueRef<'a> {
+    Nil,
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Int128(i128),
+    UInt128(u128),
+    #[cfg(feature = "bigint")]
+    BigInt(::num_bigint::BigInt),
+    Double(f64),
+    String(Cow<'a, str>),
+    Data(Vec<u8>),
+    Date(f64),
+    Array(Vec<ValueRef<'a>>),
+    Map(Vec<(ValueRef<'a>, ValueRef<'a>)>),
+}
+
+impl<'a> ValueRef<'a> {
+    /// Copies the borrowed strings into a `Value`. The last of duplicate keys wins.
+    pub fn into_owned(self) -> Value {
+        match self {
+            ValueRef::Nil => Value::Nil,
+            ValueRef::Bool(b) => Value::Bool(b),
+            ValueRef::Int(i) => Value::Int(i),
+            ValueRef::UInt(u) => Value::UInt(u),
+            ValueRef::Int128(i) => Value::Int128(i),
+            ValueRef::UInt128(u) => Value::UInt128(u),
+            #[cfg(feature = "bigint")]
+            ValueRef::BigInt(b) => Value::BigInt(b),
+            ValueRef::Double(f) => Value::Double(f),
+            ValueRef::String(s) => Value::String(s.into_owned()),
+            ValueRef::Data(d) => Value::Data(d),
+            ValueRef::Date(f) => Value::Date(f),
+            ValueRef::Array(a) => Value::Array(Array {
+                contents: a.into_iter().map(ValueRef::into_owned).collect(),
+            }),
+            ValueRef::Map(m) => {
+                let mut map = Map::with_capacity(m.len());
+                for (key, value) in m {
+                    map.insert(key.into_owned(), value.into_owned());
+                }
+                Value::Map(map)
+            }
+        }
+    }
+}
+
+fn integer_ref<'a>(v: ParseResult) -> ValueRef<'a> {
+    match integer(v) {
+        Value::Int(i) => ValueRef::Int(i),
+        Value::UInt(u) => ValueRef::UInt(u),
+        Value::Int128(i) => ValueRef::Int128(i),
+        Value::UInt128(u) => ValueRef::UInt128(u),
+        _ => unreachable!(),
+    }
+}
+
+impl<'de> Deserialize<'de> for ValueRef<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ValueRefVisitor;
+
+        impl<'de> Visitor<'de> for ValueRefVisitor {
+            type Value = ValueRef<'de>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "any valid SION value")
+            }
+
+            fn visit_bool<E>(self, value: bool) -> Result<ValueRef<'de>, E> {
+                Ok(ValueRef::Bool(value))
+            }
+
+            fn visit_i64<E>(self, value: i64) -> Result<ValueRef<'de>, E> {
+                Ok(ValueRef::Int(value))
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<ValueRef<'de>, E> {
+                Ok(integer_ref(ParseResult::from_u128(u128::from(value))))
+            }
+
+            fn visit_i128<E>(self, value: i128) -> Result<ValueRef<'de>, E> {
+                Ok(integer_ref(ParseResult::from_i128(value)))
+            }
+
+            fn visit_u128<E>(self, value: u128) -> Result<ValueRef<'de>, E> {
+                Ok(integer_ref(ParseResult::from_u128(value)))
+            }
+
+            fn visit_f64<E>(self, value: f64) -> Result<ValueRef<'de>, E> {
+                Ok(ValueRef::Double(value))
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<ValueRef<'de>, E> {
+                Ok(ValueRef::String(Cow::Owned(value.to_string())))
+            }
+
+            fn visit_borrowed_str<E>(self, value: &'de str) -> Result<ValueRef<'de>, E> {
+                Ok(ValueRef::String(Cow::Borrowed(value)))
+            }
+
+            fn visit_string<E>(self, value: String) -> Result<ValueRef<'de>, E> {
+                Ok(ValueRef::String(Cow::Owned(value)))
+            }
+
+            fn visit_bytes<E>(self, value: &[u8]) -> Result<ValueRef<'de>, E> {
+                Ok(ValueRef::Data(value.to_vec()))
+            }
+
+            fn visit_byte_buf<E>(self, value: Vec<u8>) -> Result<ValueRef<'de>, E> {
+                Ok(ValueRef::Data(value))
+            }
+
+            fn visit_none<E>(self) -> Result<ValueRef<'de>, E> {
+                Ok(ValueRef::Nil)
+            }
+
+            fn visit_some<D>(self, deserializer: D) -> Result<ValueRef<'de>, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                Deserialize::deserialize(deserializer)
+            }
+
+            fn visit_unit<E>(self) -> Result<ValueRef<'de>, E> {
+                Ok(ValueRef::Nil)
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<ValueRef<'de>, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let mut contents = vec![];
+                while let Some(v) = seq.next_element()? {
+                    contents.push(v);
+                }
+                Ok(ValueRef::Array(contents))
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<ValueRef<'de>, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut contents = vec![];
+                match map.next_key()? {
+                    Some(ValueRef::String(ref key)) if key == ::date::FIELD => {
+                        return Ok(ValueRef::Date(map.next_value()?));
+                    }
+                    #[cfg(feature = "bigint")]
+                    Some(ValueRef::String(ref key)) if key == ::number::BIGINT_FIELD => {
+                        let digits: String = map.next_value()?;
+                        return digits
+                            .parse()
+                            .map(ValueRef::BigInt)
+                            .map_err(de::Error::custom);
+                    }
+                    Some(key) => contents.push((key, map.next_value()?)),
+                    None => return Ok(ValueRef::Map(contents)),
+                }
+                while let Some(entry) = map.next_entry()? {
+                    contents.push(entry);
+                }
+                Ok(ValueRef::Map(contents))
+            }
+        }
+
+        deserializer.deserialize_any(ValueRefVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_value_ref() {
+        use std::borrow::Cow;
+        use value::borrowed::ValueRef::{self, *};
+        use value::Value;
+        use {from_slice, from_str};
+
+        let input = r#"["name": "sion", "escaped\n": "a\tb", 1: [nil, .Date(0.0), .Data("AQI=")], "name": "x"]"#;
+        let value: ValueRef = from_str(input).unwrap();
+        let expected = Map(vec![
+            (String("name".into()), String("sion".into())),
+            (String("escaped\n".into()), String("a\tb".into())),
+            (Int(1), Array(vec![Nil, Date(0.0), Data(vec![1, 2])])),
+            (String("name".into()), String("x".into())),
+        ]);
+        assert_eq!(value, expected);
+
+        match value {
+            Map(ref m) => {
+                match m[0] {
+                    (String(Cow::Borrowed("name")), String(Cow::Borrowed("sion"))) => {}
+                    ref v => panic!("{:?}", v),
+                }
+                match m[1] {
+                    (String(Cow::Owned(_)), String(Cow::Owned(_))) => {}
+                    ref v => panic!("{:?}", v),
+                }
+            }
+            ref v => panic!("{:?}", v),
+        }
+        assert_eq!(value.into_owned(), from_str::<Value>(input).unwrap());
+
+        let value: ValueRef = from_slice(b"[-1, 18446744073709551615, [:], []]").unwrap();
+        assert_eq!(
+            value,
+            Array(vec![Int(-1), UInt(u64::MAX), Map(vec![]), Array(vec![])])
+        );
+        assert_eq!(
+            from_str::<ValueRef>("\"a\"").unwrap().into_owned(),
+            Value::String("a".into())
+        );
+    }
+}
@@ -0,0 +1,307 @@
+use super::Value;
+use indexmap::Equivalent;
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+// Integers of any variant, normalized so that equal numbers compare equal.
+// The derived order of the variants follows their ranges.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Integer<'a> {
+    #[cfg(feature = "bigint")]
+    BelowI128(&'a ::num_bigint::BigInt),
+    I128(i128),
+    AboveI128(u128),
+    #[cfg(feature = "bigint")]
+    AboveU128(&'a ::num_bigint::BigInt),
+    // keeps 'a used without the bigint feature
+    #[cfg(not(feature = "bigint"))]
+    #[allow(dead_code)]
+    Never(::std::marker::PhantomData<&'a ()>),
+}
+
+impl<'a> Integer<'a> {
+    fn from_u128(u: u128) -> Self {
+        if u <= i128::MAX as u128 {
+            Integer::I128(u as i128)
+        } else {
+            Integer::AboveI128(u)
+        }
+    }
+
+    #[cfg(feature = "bigint")]
+    fn from_big(b: &'a ::num_bigint::BigInt) -> Self {
+        use num_bigint::Sign;
+        use std::convert::TryFrom;
+
+        if let Ok(i) = i128::try_from(b) {
+            Integer::I128(i)
+        } else if let Ok(u) = u128::try_from(b) {
+            Integer::AboveI128(u)
+        } else if b.sign() == Sign::Minus {
+            Integer::BelowI128(b)
+        } else {
+            Integer::AboveU128(b)
+        }
+    }
+}
+
+// A double that is equal to itself even if NaN, and whose zeros are equal.
+struct Double(f64);
+
+impl Double {
+    fn canonical_bits(&self) -> u64 {
+        if self.0.is_nan() {
+            f64::NAN.to_bits()
+        } else if self.0 == 0.0 {
+            0.0_f64.to_bits()
+        } else {
+            self.0.to_bits()
+        }
+    }
+
+    fn cmp(&self, other: &Double) -> Ordering {
+        match (self.0.is_nan(), other.0.is_nan()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => self.0.partial_cmp(&other.0).unwrap(),
+        }
+    }
+}
+
+impl Value {
+    fn rank(&self) -> u8 {
+        use self::Value::*;
+
+        match self {
+            Nil => 0,
+            Bool(_) => 1,
+            Int(_) | UInt(_) | Int128(_) | UInt128(_) => 2,
+            #[cfg(feature = "bigint")]
+            BigInt(_) => 2,
+            Double(_) => 3,
+            String(_) => 4,
+            Data(_) => 5,
+            Date(_) => 6,
+            Array(_) => 7,
+            Map(_) => 8,
+        }
+    }
+
+    fn integer(&self) -> Option<Integer<'_>> {
+        use self::Value::*;
+
+        match self {
+            Int(i) => Some(Integer::I128(i128::from(*i))),
+            UInt(u) => Some(Integer::I128(i128::from(*u))),
+            Int128(i) => Some(Integer::I128(*i)),
+            UInt128(u) => Some(Integer::from_u128(*u)),
+            #[cfg(feature = "bigint")]
+            BigInt(b) => Some(Integer::from_big(b)),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Value {}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Value) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Value {
+    fn cmp(&self, other: &Value) -> Ordering {
+        use self::Value::*;
+
+        match (self, other) {
+            (Bool(a), Bool(b)) => a.cmp(b),
+            (Double(a), Double(b)) | (Date(a), Date(b)) => self::Double(*a).cmp(&self::Double(*b)),
+            (String(a), String(b)) => a.cmp(b),
+            (Data(a), Data(b)) => a.cmp(b),
+            (Array(a), Array(b)) => a.cmp(b),
+            (Map(a), Map(b)) => a.cmp(b),
+            _ => match (self.integer(), other.integer()) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                _ => self.rank().cmp(&other.rank()),
+            },
+        }
+    }
+}
+
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        use self::Value::*;
+
+        self.rank().hash(state);
+        match self {
+            Nil => {}
+            Bool(b) => b.hash(state),
+            Double(f) | Date(f) => self::Double(*f).canonical_bits().hash(state),
+            String(s) => s.hash(state),
+            Data(d) => d.hash(state),
+            Array(a) => a.hash(state),
+            Map(m) => m.hash(state),
+            _ => self.integer().hash(state),
+        }
+    }
+}
+
+// A string key that can look up `Value::String` entries of a map without allocating.
+pub(crate) struct StrKey<'a>(pub(crate) &'a str);
+
+impl<'a> Hash for StrKey<'a> {
+    // the same as `Value::String`
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Value::String(::std::string::String::new())
+            .rank()
+            .hash(state);
+        self.0.hash(state);
+    }
+}
+
+impl<'a> Equivalent<Value> for StrKey<'a> {
+    fn equivalent(&self, key: &Value) -> bool {
+        match key {
+            Value::String(s) => s == self.0,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_order() {
+        use super::super::Value::*;
+        use sequence::{Array as A, Map as M};
+
+        let ordered = vec![
+            Nil,
+            Bool(false),
+            Bool(true),
+            Int128(i128::MIN),
+            Int(-1),
+            Int(0),
+            UInt(u64::MAX),
+            UInt128(u128::MAX),
+            Double(-f64::INFINITY),
+            Double(-1.5),
+            Double(0.0),
+            Double(f64::INFINITY),
+            Double(f64::NAN),
+            String("".into()),
+            String("a".into()),
+            Data(vec![]),
+            Date(0.0),
+            Array(A { contents: vec![] }),
+            Array(A {
+                contents: vec![Nil],
+            }),
+            Map(M::new()),
+        ];
+        for (i, a) in ordered.iter().enumerate() {
+            for (j, b) in ordered.iter().enumerate() {
+                assert_eq!(a.cmp(b), i.cmp(&j), "{:?} {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_eq_and_hash() {
+        use super::super::Value::{self, *};
+        use sequence::Map as M;
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        fn hash(v: &Value) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            v.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let equal = vec![
+            (Double(f64::NAN), Double(-f64::NAN)),
+            (Double(0.0), Double(-0.0)),
+            (Date(0.0), Date(-0.0)),
+            (Int(1), UInt(1)),
+            (Int(-1), Int128(-1)),
+            (UInt(1), UInt128(1)),
+        ];
+        for (a, b) in equal {
+            assert_eq!(a, b);
+            assert_eq!(hash(&a), hash(&b));
+        }
+
+        assert_ne!(Int(0), Double(0.0));
+        assert_ne!(Double(0.0), Date(0.0));
+        // the order of map entries doesn't matter
+        let ab = Map(M {
+            contents: vec![(Int(1), Nil), (Int(2), Nil)].into_iter().collect(),
+        });
+        let ba = Map(M {
+            contents: vec![(Int(2), Nil), (Int(1), Nil)].into_iter().collect(),
+        });
+        assert_eq!(ab, ba);
+        assert_eq!(hash(&ab), hash(&ba));
+        assert_eq!(ab.cmp(&ba), ::std::cmp::Ordering::Equal);
+        let ac = Map(M {
+            contents: vec![(Int(1), Nil), (Int(3), Nil)].into_iter().collect(),
+        });
+        assert_ne!(ab, ac);
+        assert!(ba < ac);
+    }
+
+    #[test]
+    fn test_collections() {
+        use super::super::Value::{self, *};
+        use from_str;
+        use std::collections::{BTreeMap, HashSet};
+
+        let key: Value = from_str("[1: \"one\", 2.5: \"two and a half\"]").unwrap();
+        let mut map = BTreeMap::new();
+        map.insert(key.clone(), 1);
+        map.insert(Double(f64::NAN), 2);
+        assert_eq!(map[&key], 1);
+        assert_eq!(map[&Double(f64::NAN)], 2);
+
+        let set: HashSet<Value> = vec![Int(1), UInt(1), Double(0.0), Double(-0.0), key]
+            .into_iter()
+            .collect();
+        assert_eq!(set.len(), 3);
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn test_big_integer() {
+        use super::super::Value::*;
+        use num_bigint::BigInt as B;
+
+        let big = BigInt(B::from(u128::MAX) * 2);
+        assert!(UInt128(u128::MAX) < big);
+        assert!(BigInt(-B::from(u128::MAX)) < Int128(i128::MIN));
+        assert_eq!(BigInt(B::from(1)), Int(1));
+        assert_eq!(BigInt(B::from(u128::MAX)), UInt128(u128::MAX));
+    }
+
+    #[test]
+    fn test_str_key() {
+        use super::super::Value;
+        use super::StrKey;
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        let mut value = DefaultHasher::new();
+        Value::String("key".into()).hash(&mut value);
+        let mut key = DefaultHasher::new();
+        StrKey("key").hash(&mut key);
+        assert_eq!(value.finish(), key.finish());
+    }
+}
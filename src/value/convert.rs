@@ -0,0 +1,569 @@
+use super::de::integer;
+use super::index::kind;
+use super::ser::widen;
+use super::{Path, Segment, Value};
+use number::ParseResult;
+use sequence::{Array, Map};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::hash::Hash;
+use std::iter::FromIterator;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use {Error, Result};
+
+// `u8` is left out so that `Vec<u8>` converts into `Value::Data` rather than an array.
+// The other way, `u8` is taken like any integer and `Vec<T>` takes data as an array of its
+// bytes, so that `Vec<u8>` converts from both.
+macro_rules! from_integer {
+    ($($ty:ty)*) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(v: $ty) -> Value {
+                    integer(ParseResult::from_i128(v as i128))
+                }
+            }
+        )*
+    };
+}
+
+from_integer!(i8 i16 i32 i64 isize u16 u32 u64 usize);
+
+impl From<i128> for Value {
+    fn from(v: i128) -> Value {
+        integer(ParseResult::from_i128(v))
+    }
+}
+
+impl From<u128> for Value {
+    fn from(v: u128) -> Value {
+        integer(ParseResult::from_u128(v))
+    }
+}
+
+impl From<f32> for Value {
+    fn from(v: f32) -> Value {
+        Value::Double(widen(v))
+    }
+}
+
+impl From<f64> for Value {
+    fn from(v: f64) -> Value {
+        Value::Double(v)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(v: bool) -> Value {
+        Value::Bool(v)
+    }
+}
+
+impl<'a> From<&'a str> for Value {
+    fn from(v: &'a str) -> Value {
+        Value::String(v.into())
+    }
+}
+
+impl From<String> for Value {
+    fn from(v: String) -> Value {
+        Value::String(v)
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    fn from(v: Vec<u8>) -> Value {
+        Value::Data(v)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(v: Vec<T>) -> Value {
+        v.into_iter().collect()
+    }
+}
+
+impl<K: Into<Value>, V: Into<Value>, S> From<HashMap<K, V, S>> for Value {
+    fn from(v: HashMap<K, V, S>) -> Value {
+        v.into_iter().collect()
+    }
+}
+
+impl<K: Into<Value>, V: Into<Value>> From<BTreeMap<K, V>> for Value {
+    fn from(v: BTreeMap<K, V>) -> Value {
+        v.into_iter().collect()
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(v: Option<T>) -> Value {
+        v.map_or(Value::Nil, Into::into)
+    }
+}
+
+// a time before the epoch is a negative timestamp
+impl From<SystemTime> for Value {
+    fn from(v: SystemTime) -> Value {
+        match v.duration_since(UNIX_EPOCH) {
+            Ok(d) => Value::Date(d.as_secs_f64()),
+            Err(e) => Value::Date(-e.duration().as_secs_f64()),
+        }
+    }
+}
+
+impl From<::Date> for Value {
+    fn from(v: ::Date) -> Value {
+        Value::Date(v.timestamp())
+    }
+}
+
+impl From<Array> for Value {
+    fn from(v: Array) -> Value {
+        Value::Array(v)
+    }
+}
+
+impl From<Map> for Value {
+    fn from(v: Map) -> Value {
+        Value::Map(v)
+    }
+}
+
+impl<T: Into<Value>> FromIterator<T> for Array {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Array {
+        Array {
+            contents: iter.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+// the last of duplicate keys wins, as with `Map::insert`
+impl<K: Into<Value>, V: Into<Value>> FromIterator<(K, V)> for Map {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Map {
+        Map {
+            contents: iter
+                .into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        }
+    }
+}
+
+impl<T: Into<Value>> FromIterator<T> for Value {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Value {
+        Value::Array(iter.into_iter().collect())
+    }
+}
+
+impl<K: Into<Value>, V: Into<Value>> FromIterator<(K, V)> for Value {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Value {
+        Value::Map(iter.into_iter().collect())
+    }
+}
+
+fn invalid(expected: &'static str, v: &Value) -> Error {
+    // numbers and dates are shown as they are, to tell why they are out of range
+    let found = match v {
+        Value::Int(_)
+        | Value::UInt(_)
+        | Value::Int128(_)
+        | Value::UInt128(_)
+        | Value::Double(_)
+        | Value::Date(_) => ::to_string(v).unwrap_or_else(|_| kind(v).to_string()),
+        #[cfg(feature = "bigint")]
+        Value::BigInt(_) => ::to_string(v).unwrap_or_else(|_| kind(v).to_string()),
+        Value::Data(d) => format!("{} of {} bytes", kind(v), d.len()),
+        _ => kind(v).to_string(),
+    };
+    Error::InvalidValue {
+        path: Path::root(),
+        expected,
+        found,
+    }
+}
+
+// puts `segment` in front of the path of an error from converting a child
+fn within(e: Error, segment: Segment) -> Error {
+    match e {
+        Error::InvalidValue {
+            path,
+            expected,
+            found,
+        } => {
+            let mut prefixed = Path::root().join(segment);
+            for segment in path.segments() {
+                prefixed.push(segment.clone());
+            }
+            Error::InvalidValue {
+                path: prefixed,
+                expected,
+                found,
+            }
+        }
+        e => e,
+    }
+}
+
+macro_rules! try_from_integer {
+    ($($ty:ident)*) => {
+        $(
+            impl TryFrom<Value> for $ty {
+                type Error = Error;
+
+                fn try_from(v: Value) -> Result<$ty> {
+                    let converted = match v {
+                        Value::Int(i) => $ty::try_from(i).ok(),
+                        Value::UInt(u) => $ty::try_from(u).ok(),
+                        Value::Int128(i) => $ty::try_from(i).ok(),
+                        Value::UInt128(u) => $ty::try_from(u).ok(),
+                        #[cfg(feature = "bigint")]
+                        Value::BigInt(_) => None,
+                        _ => return Err(invalid("an integer", &v)),
+                    };
+                    let expected = concat!("an integer in the range of ", stringify!($ty));
+                    converted.ok_or_else(|| invalid(expected, &v))
+                }
+            }
+        )*
+    };
+}
+
+try_from_integer!(i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize);
+
+// integers are converted to the nearest double
+fn double(v: &Value) -> Result<f64> {
+    match *v {
+        Value::Double(f) => Ok(f),
+        Value::Int(i) => Ok(i as f64),
+        Value::UInt(u) => Ok(u as f64),
+        Value::Int128(i) => Ok(i as f64),
+        Value::UInt128(u) => Ok(u as f64),
+        #[cfg(feature = "bigint")]
+        Value::BigInt(ref b) => match b.to_string().parse::<f64>() {
+            Ok(f) if f.is_finite() => Ok(f),
+            _ => Err(invalid("a double in the range of f64", v)),
+        },
+        _ => Err(invalid("a double", v)),
+    }
+}
+
+impl TryFrom<Value> for f64 {
+    type Error = Error;
+
+    fn try_from(v: Value) -> Result<f64> {
+        double(&v)
+    }
+}
+
+// only finite values beyond the range of `f32` fail
+impl TryFrom<Value> for f32 {
+    type Error = Error;
+
+    fn try_from(v: Value) -> Result<f32> {
+        let f = double(&v)?;
+        if f.is_finite() && (f as f32).is_infinite() {
+            Err(invalid("a double in the range of f32", &v))
+        } else {
+            Ok(f as f32)
+        }
+    }
+}
+
+impl TryFrom<Value> for bool {
+    type Error = Error;
+
+    fn try_from(v: Value) -> Result<bool> {
+        match v {
+            Value::Bool(b) => Ok(b),
+            _ => Err(invalid("a boolean", &v)),
+        }
+    }
+}
+
+impl TryFrom<Value> for String {
+    type Error = Error;
+
+    fn try_from(v: Value) -> Result<String> {
+        match v {
+            Value::String(s) => Ok(s),
+            _ => Err(invalid("a string", &v)),
+        }
+    }
+}
+
+impl<T> TryFrom<Value> for Vec<T>
+where
+    T: TryFrom<Value, Error = Error>,
+{
+    type Error = Error;
+
+    fn try_from(v: Value) -> Result<Vec<T>> {
+        match v {
+            Value::Array(a) => a
+                .contents
+                .into_iter()
+                .enumerate()
+                .map(|(i, v)| T::try_from(v).map_err(|e| within(e, Segment::Index(i))))
+                .collect(),
+            Value::Data(d) => d
+                .into_iter()
+                .enumerate()
+                .map(|(i, b)| {
+                    T::try_from(Value::Int(i64::from(b))).map_err(|e| within(e, Segment::Index(i)))
+                })
+                .collect(),
+            _ => Err(invalid("an array", &v)),
+        }
+    }
+}
+
+// The value of an entry is converted first, so that its key can be named in the path of an
+// error. A key is used up by its own conversion, so an error from it is reported at the map.
+fn try_from_map<K, V, C>(v: Value) -> Result<C>
+where
+    K: TryFrom<Value, Error = Error>,
+    V: TryFrom<Value, Error = Error>,
+    C: FromIterator<(K, V)>,
+{
+    match v {
+        Value::Map(m) => m
+            .contents
+            .into_iter()
+            .map(|(k, v)| {
+                let v = V::try_from(v).map_err(|e| within(e, Segment::Key(k.clone())))?;
+                Ok((K::try_from(k)?, v))
+            })
+            .collect(),
+        _ => Err(invalid("a map", &v)),
+    }
+}
+
+impl<K, V> TryFrom<Value> for HashMap<K, V>
+where
+    K: TryFrom<Value, Error = Error> + Eq + Hash,
+    V: TryFrom<Value, Error = Error>,
+{
+    type Error = Error;
+
+    fn try_from(v: Value) -> Result<HashMap<K, V>> {
+        try_from_map(v)
+    }
+}
+
+impl<K, V> TryFrom<Value> for BTreeMap<K, V>
+where
+    K: TryFrom<Value, Error = Error> + Ord,
+    V: TryFrom<Value, Error = Error>,
+{
+    type Error = Error;
+
+    fn try_from(v: Value) -> Result<BTreeMap<K, V>> {
+        try_from_map(v)
+    }
+}
+
+impl<T> TryFrom<Value> for Option<T>
+where
+    T: TryFrom<Value, Error = Error>,
+{
+    type Error = Error;
+
+    fn try_from(v: Value) -> Result<Option<T>> {
+        match v {
+            Value::Nil => Ok(None),
+            v => T::try_from(v).map(Some),
+        }
+    }
+}
+
+impl TryFrom<Value> for SystemTime {
+    type Error = Error;
+
+    fn try_from(v: Value) -> Result<SystemTime> {
+        let time = match v {
+            Value::Date(t) if t >= 0.0 => Duration::try_from_secs_f64(t)
+                .ok()
+                .and_then(|d| UNIX_EPOCH.checked_add(d)),
+            Value::Date(t) => Duration::try_from_secs_f64(-t)
+                .ok()
+                .and_then(|d| UNIX_EPOCH.checked_sub(d)),
+            _ => return Err(invalid("a date", &v)),
+        };
+        time.ok_or_else(|| invalid("a date in the range of SystemTime", &v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_from() {
+        use sequence::{Array, Map};
+        use std::collections::{BTreeMap, HashMap};
+        use std::time::{Duration, UNIX_EPOCH};
+        use value::Value::{self, *};
+
+        assert_eq!(Value::from(42), Int(42));
+        assert_eq!(Value::from(u64::MAX), UInt(u64::MAX));
+        assert_eq!(Value::from(1u128), Int(1));
+        assert_eq!(Value::from(1.5), Double(1.5));
+        assert_eq!(Value::from(0.1f32), Double(0.1));
+        assert_eq!(Value::from(true), Bool(true));
+        assert_eq!(Value::from("x"), String("x".into()));
+        assert_eq!(Value::from(vec![1u8, 2]), Data(vec![1, 2]));
+        assert_eq!(Value::from(vec!["a", "b"]), sion!(["a", "b"]));
+        assert_eq!(Value::from(Some(1)), Int(1));
+        assert_eq!(Value::from(None::<i64>), Nil);
+        assert_eq!(
+            Value::from(UNIX_EPOCH + Duration::from_millis(1500)),
+            Date(1.5)
+        );
+        assert_eq!(Value::from(UNIX_EPOCH - Duration::from_secs(2)), Date(-2.0));
+
+        let mut btree = BTreeMap::new();
+        btree.insert("b", vec![1]);
+        btree.insert("a", vec![]);
+        assert_eq!(Value::from(btree), sion!(["a": [], "b": [1]]));
+        let mut hash = HashMap::new();
+        hash.insert(1, "one");
+        assert_eq!(Value::from(hash), sion!([1: "one"]));
+
+        let array: Array = (1..4).collect();
+        assert_eq!(Value::from(array), sion!([1, 2, 3]));
+        let map: Map = vec![("a", 1), ("b", 2), ("a", 3)].into_iter().collect();
+        assert_eq!(Value::from(map), sion!(["a": 3, "b": 2]));
+        let value: Value = vec![(Nil, "x")].into_iter().collect();
+        assert_eq!(value, sion!([nil: "x"]));
+        let value: Value = vec![Nil, Bool(false)].into_iter().collect();
+        assert_eq!(value, sion!([nil, false]));
+    }
+
+    #[test]
+    fn test_try_from() {
+        use std::collections::{BTreeMap, HashMap};
+        use std::convert::TryFrom;
+        use std::time::{Duration, SystemTime, UNIX_EPOCH};
+        use value::Value::*;
+
+        assert_eq!(i64::try_from(Int(-1)).unwrap(), -1);
+        assert_eq!(u32::try_from(Int(80)).unwrap(), 80);
+        assert_eq!(u64::try_from(UInt(u64::MAX)).unwrap(), u64::MAX);
+        assert_eq!(f64::try_from(Double(1.5)).unwrap(), 1.5);
+        assert_eq!(f64::try_from(Int(2)).unwrap(), 2.0);
+        assert!(bool::try_from(Bool(true)).unwrap());
+        assert_eq!(::std::string::String::try_from(sion!("x")).unwrap(), "x");
+        assert_eq!(Vec::<u8>::try_from(Data(vec![1])).unwrap(), vec![1]);
+        assert_eq!(Vec::<u8>::try_from(sion!([1, 255])).unwrap(), vec![1, 255]);
+        assert_eq!(Vec::<u32>::try_from(Data(vec![1])).unwrap(), vec![1]);
+        assert_eq!(u8::try_from(Int(255)).unwrap(), 255);
+        assert_eq!(f32::try_from(Double(0.1)).unwrap(), 0.1);
+        assert_eq!(f32::try_from(Int(3)).unwrap(), 3.0);
+        assert!(f32::try_from(Double(f64::INFINITY)).unwrap().is_infinite());
+        assert_eq!(Vec::<i64>::try_from(sion!([1, 2])).unwrap(), vec![1, 2]);
+        assert_eq!(Option::<i64>::try_from(Nil).unwrap(), None);
+        assert_eq!(Option::<i64>::try_from(Int(1)).unwrap(), Some(1));
+        assert_eq!(
+            SystemTime::try_from(Date(-1.5)).unwrap(),
+            UNIX_EPOCH - Duration::from_millis(1500)
+        );
+
+        let map = HashMap::<::std::string::String, Vec<u32>>::try_from(sion!(["a": [1]])).unwrap();
+        assert_eq!(map["a"], vec![1]);
+        let map = BTreeMap::<i64, bool>::try_from(sion!([2: true, 1: false])).unwrap();
+        assert_eq!(
+            map.into_iter().collect::<Vec<_>>(),
+            vec![(1, false), (2, true)]
+        );
+    }
+
+    #[test]
+    fn test_try_from_error() {
+        use std::collections::BTreeMap;
+        use std::convert::TryFrom;
+        use std::time::SystemTime;
+        use value::Value::*;
+        use Error;
+
+        let message = |e: Error| e.to_string();
+        assert_eq!(
+            message(u32::try_from(sion!("80")).unwrap_err()),
+            "expected an integer, found a string"
+        );
+        assert_eq!(
+            message(i8::try_from(Int(300)).unwrap_err()),
+            "expected an integer in the range of i8, found 300"
+        );
+        assert_eq!(
+            message(Vec::<Vec<u8>>::try_from(sion!([.Data("AQI="), [1, 256]])).unwrap_err()),
+            "expected an integer in the range of u8 at /1/1, found 256"
+        );
+        assert_eq!(
+            message(::std::string::String::try_from(Data(vec![0; 3])).unwrap_err()),
+            "expected a string, found data of 3 bytes"
+        );
+        assert_eq!(
+            message(Vec::<bool>::try_from(sion!(.Data("AQI="))).unwrap_err()),
+            "expected a boolean at /0, found 1"
+        );
+        assert_eq!(
+            message(f32::try_from(Double(1e300)).unwrap_err()),
+            "expected a double in the range of f32, found 1.0e300"
+        );
+        type Servers = BTreeMap<::std::string::String, Vec<BTreeMap<::std::string::String, u16>>>;
+        let nested = sion!(["servers": [["port": 80], ["port": -1]]]);
+        let e = Servers::try_from(nested.clone()).unwrap_err();
+        match e {
+            Error::InvalidValue { ref path, .. } => {
+                assert_eq!(nested.get_path(path), Some(&Int(-1)))
+            }
+            ref e => panic!("{:?}", e),
+        }
+        assert_eq!(
+            message(e),
+            "expected an integer in the range of u16 at /servers/1/port, found -1"
+        );
+        assert_eq!(
+            message(BTreeMap::<i64, bool>::try_from(sion!(["a": true])).unwrap_err()),
+            "expected an integer, found a string"
+        );
+        type Nested = BTreeMap<::std::string::String, BTreeMap<i64, bool>>;
+        assert_eq!(
+            message(Nested::try_from(sion!(["m": [1: true, "a": true]])).unwrap_err()),
+            "expected an integer at /m, found a string"
+        );
+        assert_eq!(
+            message(SystemTime::try_from(Date(f64::NAN)).unwrap_err()),
+            "expected a date in the range of SystemTime, found .Date(NaN)"
+        );
+        assert_eq!(
+            message(f64::try_from(Nil).unwrap_err()),
+            "expected a double, found nil"
+        );
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn test_try_from_big_integer() {
+        use num_bigint::BigInt as B;
+        use std::convert::TryFrom;
+        use value::Value::*;
+
+        let big = BigInt(B::from(1) << 130);
+        assert_eq!(f64::try_from(big.clone()).unwrap(), 2.0_f64.powi(130));
+        let digits = big.to_string();
+        assert_eq!(
+            f32::try_from(big.clone()).unwrap_err().to_string(),
+            format!("expected a double in the range of f32, found {}", digits)
+        );
+        assert_eq!(
+            u128::try_from(big).unwrap_err().to_string(),
+            format!("expected an integer in the range of u128, found {}", digits)
+        );
+        let huge = BigInt(B::from(10).pow(400));
+        assert_eq!(
+            f64::try_from(huge).unwrap_err().to_string(),
+            format!(
+                "expected a double in the range of f64, found 1{}",
+                "0".repeat(400)
+            )
+        );
+    }
+}
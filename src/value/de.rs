@@ -1,9 +1,30 @@
+use super::ser::widen;
 use super::Value;
-use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use number::ParseResult;
+#[cfg(feature = "bigint")]
+use serde::de;
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{
+    Deserialize, DeserializeOwned, DeserializeSeed, Deserializer, EnumAccess, IntoDeserializer,
+    MapAccess, SeqAccess, VariantAccess, Visitor,
+};
 use std::fmt;
+use Error;
+
+pub(crate) fn integer(v: ParseResult) -> Value {
+    match v {
+        ParseResult::Int(i) => Value::Int(i),
+        ParseResult::UInt(u) => Value::UInt(u),
+        ParseResult::Int128(i) => Value::Int128(i),
+        ParseResult::UInt128(u) => Value::UInt128(u),
+        #[cfg(feature = "bigint")]
+        ParseResult::BigInt(b) => Value::BigInt(b),
+        ParseResult::Double(_) => unreachable!(),
+    }
+}
 
 impl<'de> Deserialize<'de> for Value {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
@@ -33,7 +54,7 @@ impl<'de> Deserialize<'de> for Value {
             }
 
             fn visit_i64<E>(self, value: i64) -> Result<Value, E> {
-                Ok(Value::Int(i64::from(value)))
+                Ok(Value::Int(value))
             }
 
             fn visit_u8<E>(self, value: u8) -> Result<Value, E> {
@@ -49,16 +70,23 @@ impl<'de> Deserialize<'de> for Value {
             }
 
             fn visit_u64<E>(self, value: u64) -> Result<Value, E> {
-                // TODO: return Err
-                Ok(Value::Int(value as i64))
+                Ok(integer(ParseResult::from_u128(u128::from(value))))
+            }
+
+            fn visit_i128<E>(self, value: i128) -> Result<Value, E> {
+                Ok(integer(ParseResult::from_i128(value)))
+            }
+
+            fn visit_u128<E>(self, value: u128) -> Result<Value, E> {
+                Ok(integer(ParseResult::from_u128(value)))
             }
 
             fn visit_f32<E>(self, value: f32) -> Result<Value, E> {
-                Ok(Value::Double(f64::from(value)))
+                Ok(Value::Double(widen(value)))
             }
 
             fn visit_f64<E>(self, value: f64) -> Result<Value, E> {
-                Ok(Value::Double(f64::from(value)))
+                Ok(Value::Double(value))
             }
 
             fn visit_char<E>(self, value: char) -> Result<Value, E> {
@@ -113,9 +141,24 @@ impl<'de> Deserialize<'de> for Value {
             where
                 A: MapAccess<'de>,
             {
-                let mut contents = vec![];
-                while let Some(item) = map.next_entry()? {
-                    contents.push(item);
+                let mut contents = ::indexmap::IndexMap::new();
+                match map.next_key()? {
+                    Some(Value::String(ref key)) if key == ::date::FIELD => {
+                        return Ok(Value::Date(map.next_value()?));
+                    }
+                    #[cfg(feature = "bigint")]
+                    Some(Value::String(ref key)) if key == ::number::BIGINT_FIELD => {
+                        let digits: ::std::string::String = map.next_value()?;
+                        return digits.parse().map(Value::BigInt).map_err(de::Error::custom);
+                    }
+                    Some(key) => {
+                        contents.insert(key, map.next_value()?);
+                    }
+                    None => return Ok(Value::Map(::sequence::Map { contents })),
+                }
+                // the last of duplicate keys wins
+                while let Some((key, value)) = map.next_entry()? {
+                    contents.insert(key, value);
                 }
                 Ok(Value::Map(::sequence::Map { contents }))
             }
@@ -124,3 +167,337 @@ impl<'de> Deserialize<'de> for Value {
         deserializer.deserialize_any(ValueVisitor)
     }
 }
+
+/// Deserializes a `T` out of a `Value` tree instead of text.
+pub fn from_value<T>(value: Value) -> ::Result<T>
+where
+    T: DeserializeOwned,
+{
+    T::deserialize(value)
+}
+
+fn visit_array<'de, I, V>(array: I, visitor: V) -> ::Result<V::Value>
+where
+    I: Iterator,
+    I::Item: IntoDeserializer<'de, Error>,
+    V: Visitor<'de>,
+{
+    let mut seq = SeqDeserializer::new(array);
+    let v = visitor.visit_seq(&mut seq)?;
+    seq.end()?;
+    Ok(v)
+}
+
+fn visit_map<'de, I, K, U, V>(map: I, visitor: V) -> ::Result<V::Value>
+where
+    I: Iterator<Item = (K, U)>,
+    K: IntoDeserializer<'de, Error>,
+    U: IntoDeserializer<'de, Error>,
+    V: Visitor<'de>,
+{
+    let mut map = MapDeserializer::new(map);
+    let v = visitor.visit_map(&mut map)?;
+    map.end()?;
+    Ok(v)
+}
+
+// `.Date(...)` and big integers reach visitors as maps with a private key, like from text
+fn visit_date<'de, V>(timestamp: f64, visitor: V) -> ::Result<V::Value>
+where
+    V: Visitor<'de>,
+{
+    visit_map(::std::iter::once((::date::FIELD, timestamp)), visitor)
+}
+
+// a `RawValue` gets the value written as text
+fn visit_raw<'de, V>(sion: String, visitor: V) -> ::Result<V::Value>
+where
+    V: Visitor<'de>,
+{
+    visit_map(::std::iter::once((::raw::FIELD, sion)), visitor)
+}
+
+// a `Number` gets the literal of an integer or a double
+#[cfg(feature = "arbitrary_precision")]
+fn visit_number<'de, V>(value: &Value, visitor: V) -> ::Result<V::Value>
+where
+    V: Visitor<'de>,
+{
+    match value {
+        Value::Int(_)
+        | Value::UInt(_)
+        | Value::Int128(_)
+        | Value::UInt128(_)
+        | Value::Double(_) => {}
+        #[cfg(feature = "bigint")]
+        Value::BigInt(_) => {}
+        _ => return Err(Error::Expected("a number".into())),
+    }
+    let entry = (::number::NUMBER_FIELD, ::to_string(value)?);
+    visit_map(::std::iter::once(entry), visitor)
+}
+
+#[cfg(feature = "bigint")]
+fn visit_big_integer<'de, V>(b: &::num_bigint::BigInt, visitor: V) -> ::Result<V::Value>
+where
+    V: Visitor<'de>,
+{
+    visit_map(
+        ::std::iter::once((::number::BIGINT_FIELD, b.to_string())),
+        visitor,
+    )
+}
+
+// the single entry of `["Variant": value]`
+fn enum_entry<I: Iterator>(mut entries: I) -> ::Result<I::Item> {
+    let entry = entries.next().ok_or(Error::ExpectedEnum)?;
+    match entries.next() {
+        Some(_) => Err(Error::UnexpectedEnumEntry),
+        None => Ok(entry),
+    }
+}
+
+impl<'de> Deserializer<'de> for Value {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> ::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Nil => visitor.visit_unit(),
+            Value::Bool(b) => visitor.visit_bool(b),
+            Value::Int(i) => visitor.visit_i64(i),
+            Value::UInt(u) => visitor.visit_u64(u),
+            Value::Int128(i) => visitor.visit_i128(i),
+            Value::UInt128(u) => visitor.visit_u128(u),
+            #[cfg(feature = "bigint")]
+            Value::BigInt(b) => visit_big_integer(&b, visitor),
+            Value::Double(f) => visitor.visit_f64(f),
+            Value::String(s) => visitor.visit_string(s),
+            Value::Data(d) => visitor.visit_byte_buf(d),
+            Value::Date(d) => visit_date(d, visitor),
+            Value::Array(a) => visit_array(a.contents.into_iter(), visitor),
+            Value::Map(m) => visit_map(m.contents.into_iter(), visitor),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> ::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Nil => visitor.visit_none(),
+            v => visitor.visit_some(v),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> ::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        #[cfg(feature = "arbitrary_precision")]
+        {
+            if name == ::number::NUMBER_NAME {
+                return visit_number(&self, visitor);
+            }
+        }
+        if name == ::date::NAME {
+            match self {
+                Value::Date(d) => visit_date(d, visitor),
+                _ => Err(Error::ExpectedDate),
+            }
+        } else if name == ::raw::NAME {
+            visit_raw(::to_string(&self)?, visitor)
+        } else {
+            visitor.visit_newtype_struct(self)
+        }
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> ::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            Value::Map(m) => {
+                let (variant, value) = enum_entry(m.contents.into_iter())?;
+                visitor.visit_enum(Enum { variant, value })
+            }
+            _ => Err(Error::ExpectedEnum),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+impl<'de> Deserializer<'de> for &'de Value {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> ::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Nil => visitor.visit_unit(),
+            Value::Bool(b) => visitor.visit_bool(*b),
+            Value::Int(i) => visitor.visit_i64(*i),
+            Value::UInt(u) => visitor.visit_u64(*u),
+            Value::Int128(i) => visitor.visit_i128(*i),
+            Value::UInt128(u) => visitor.visit_u128(*u),
+            #[cfg(feature = "bigint")]
+            Value::BigInt(b) => visit_big_integer(b, visitor),
+            Value::Double(f) => visitor.visit_f64(*f),
+            Value::String(s) => visitor.visit_borrowed_str(s),
+            Value::Data(d) => visitor.visit_borrowed_bytes(d),
+            Value::Date(d) => visit_date(*d, visitor),
+            Value::Array(a) => visit_array(a.iter(), visitor),
+            Value::Map(m) => visit_map(m.iter(), visitor),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> ::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Nil => visitor.visit_none(),
+            v => visitor.visit_some(v),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> ::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        #[cfg(feature = "arbitrary_precision")]
+        {
+            if name == ::number::NUMBER_NAME {
+                return visit_number(self, visitor);
+            }
+        }
+        if name == ::date::NAME {
+            match self {
+                Value::Date(d) => visit_date(*d, visitor),
+                _ => Err(Error::ExpectedDate),
+            }
+        } else if name == ::raw::NAME {
+            visit_raw(::to_string(&self)?, visitor)
+        } else {
+            visitor.visit_newtype_struct(self)
+        }
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> ::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::String(variant) => visitor.visit_enum(variant.as_str().into_deserializer()),
+            Value::Map(m) => {
+                let (variant, value) = enum_entry(m.iter())?;
+                visitor.visit_enum(Enum { variant, value })
+            }
+            _ => Err(Error::ExpectedEnum),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Value {
+    type Deserializer = Value;
+
+    fn into_deserializer(self) -> Value {
+        self
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for &'de Value {
+    type Deserializer = &'de Value;
+
+    fn into_deserializer(self) -> &'de Value {
+        self
+    }
+}
+
+// the variant and value of `[variant: value]`, either owned or borrowed
+struct Enum<D> {
+    variant: D,
+    value: D,
+}
+
+impl<'de, D> EnumAccess<'de> for Enum<D>
+where
+    D: Deserializer<'de, Error = Error> + VariantAccess<'de, Error = Error>,
+{
+    type Error = Error;
+    type Variant = D;
+
+    fn variant_seed<S>(self, seed: S) -> ::Result<(S::Value, D)>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(self.variant)?;
+        Ok((variant, self.value))
+    }
+}
+
+macro_rules! variant_access {
+    ($ty:ty) => {
+        impl<'de> VariantAccess<'de> for $ty {
+            type Error = Error;
+
+            // `["Variant": nil]` is accepted as a unit variant
+            fn unit_variant(self) -> ::Result<()> {
+                Deserialize::deserialize(self)
+            }
+
+            fn newtype_variant_seed<T>(self, seed: T) -> ::Result<T::Value>
+            where
+                T: DeserializeSeed<'de>,
+            {
+                seed.deserialize(self)
+            }
+
+            fn tuple_variant<V>(self, _len: usize, visitor: V) -> ::Result<V::Value>
+            where
+                V: Visitor<'de>,
+            {
+                self.deserialize_seq(visitor)
+            }
+
+            fn struct_variant<V>(
+                self,
+                _fields: &'static [&'static str],
+                visitor: V,
+            ) -> ::Result<V::Value>
+            where
+                V: Visitor<'de>,
+            {
+                self.deserialize_map(visitor)
+            }
+        }
+    };
+}
+
+variant_access!(Value);
+variant_access!(&'de Value);
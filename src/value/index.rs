@@ -0,0 +1,246 @@
+use super::Value;
+use std::ops;
+
+static NIL: Value = Value::Nil;
+
+/// Types that can index into a `Value`: `usize` for arrays, and strings or values for maps.
+///
+/// This trait is sealed and cannot be implemented outside of this crate.
+pub trait Index: private::Sealed {
+    #[doc(hidden)]
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value>;
+
+    #[doc(hidden)]
+    fn index_into_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value>;
+
+    // panics if `v` cannot be indexed by `self`
+    #[doc(hidden)]
+    fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value;
+}
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for usize {}
+    impl Sealed for str {}
+    impl Sealed for String {}
+    impl Sealed for ::value::Value {}
+    impl<T: ?Sized + Sealed> Sealed for &T {}
+}
+
+pub(super) fn kind(v: &Value) -> &'static str {
+    use self::Value::*;
+
+    match v {
+        Nil => "nil",
+        Bool(_) => "a boolean",
+        Int(_) | UInt(_) | Int128(_) | UInt128(_) => "an integer",
+        #[cfg(feature = "bigint")]
+        BigInt(_) => "an integer",
+        Double(_) => "a double",
+        String(_) => "a string",
+        Data(_) => "data",
+        Date(_) => "a date",
+        Array(_) => "an array",
+        Map(_) => "a map",
+    }
+}
+
+impl Index for usize {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        match v {
+            Value::Array(a) => a.get(*self),
+            _ => None,
+        }
+    }
+
+    fn index_into_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value> {
+        match v {
+            Value::Array(a) => a.get_mut(*self),
+            _ => None,
+        }
+    }
+
+    fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value {
+        match v {
+            Value::Array(a) => {
+                let len = a.len();
+                a.get_mut(*self).unwrap_or_else(|| {
+                    panic!(
+                        "index {} out of bounds for an array of length {}",
+                        self, len
+                    )
+                })
+            }
+            _ => panic!("cannot index into {} with {}", kind(v), self),
+        }
+    }
+}
+
+impl Index for Value {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        match v {
+            Value::Map(m) => m.get(self),
+            _ => None,
+        }
+    }
+
+    fn index_into_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value> {
+        match v {
+            Value::Map(m) => m.get_mut(self),
+            _ => None,
+        }
+    }
+
+    fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value {
+        if let Value::Nil = v {
+            *v = Value::Map(Default::default());
+        }
+        match v {
+            Value::Map(m) => m.contents.entry(self.clone()).or_insert(Value::Nil),
+            _ => panic!("cannot index into {} with {:?}", kind(v), self),
+        }
+    }
+}
+
+impl Index for str {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        match v {
+            Value::Map(m) => m.get_str(self),
+            _ => None,
+        }
+    }
+
+    fn index_into_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value> {
+        match v {
+            Value::Map(m) => m.get_str_mut(self),
+            _ => None,
+        }
+    }
+
+    fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value {
+        Value::String(self.into()).index_or_insert(v)
+    }
+}
+
+impl Index for String {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        self[..].index_into(v)
+    }
+
+    fn index_into_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value> {
+        self[..].index_into_mut(v)
+    }
+
+    fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value {
+        self[..].index_or_insert(v)
+    }
+}
+
+impl<T: ?Sized + Index> Index for &T {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        (**self).index_into(v)
+    }
+
+    fn index_into_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value> {
+        (**self).index_into_mut(v)
+    }
+
+    fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value {
+        (**self).index_or_insert(v)
+    }
+}
+
+impl Value {
+    /// Returns the element of an array or the value of a map entry, if any.
+    pub fn get<I: Index>(&self, index: I) -> Option<&Value> {
+        index.index_into(self)
+    }
+
+    pub fn get_mut<I: Index>(&mut self, index: I) -> Option<&mut Value> {
+        index.index_into_mut(self)
+    }
+}
+
+// Reading a missing entry yields `Nil`.
+impl<I: Index> ops::Index<I> for Value {
+    type Output = Value;
+
+    fn index(&self, index: I) -> &Value {
+        index.index_into(self).unwrap_or(&NIL)
+    }
+}
+
+// Writing to a missing map entry inserts it, turning `Nil` into a map first.
+// Panics when the array index is out of bounds or the value is neither.
+impl<I: Index> ops::IndexMut<I> for Value {
+    fn index_mut(&mut self, index: I) -> &mut Value {
+        index.index_or_insert(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_index() {
+        use from_str;
+        use value::Value::{self, *};
+
+        let value: Value =
+            from_str(r#"["name": "sion", "tags": ["a", "b"], 1: "one", [2]: nil]"#).unwrap();
+        assert_eq!(value["name"], String("sion".into()));
+        assert_eq!(value[&"name".to_string()], String("sion".into()));
+        assert_eq!(value["tags"][1], String("b".into()));
+        assert_eq!(value[&Int(1)], String("one".into()));
+        assert_eq!(value[Int(1)], String("one".into()));
+        assert_eq!(value[&from_str::<Value>("[2]").unwrap()], Nil);
+
+        assert_eq!(value["missing"], Nil);
+        assert_eq!(value["tags"][2], Nil);
+        assert_eq!(value["name"]["nested"][0], Nil);
+        assert_eq!(value.get("missing"), None);
+        assert_eq!(value["tags"].get(0), Some(&String("a".into())));
+    }
+
+    #[test]
+    fn test_index_mut() {
+        use from_str;
+        use value::Value::{self, *};
+
+        let mut value: Value = from_str(r#"["tags": ["a", "b"]]"#).unwrap();
+        value["tags"][0] = String("c".into());
+        value["new"]["nested"] = Int(1);
+        value[Int(2)] = Bool(true);
+        assert_eq!(
+            value,
+            from_str::<Value>(r#"["tags": ["c", "b"], "new": ["nested": 1], 2: true]"#).unwrap()
+        );
+
+        let mut nil = Nil;
+        nil["key"] = Nil;
+        assert_eq!(nil, from_str::<Value>(r#"["key": nil]"#).unwrap());
+
+        if let Some(v) = value.get_mut("new") {
+            *v = Nil;
+        }
+        assert_eq!(value["new"], Nil);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn test_index_out_of_bounds() {
+        use from_str;
+        use value::Value;
+
+        let mut value: Value = from_str(r#"["a", "b"]"#).unwrap();
+        value[2] = Value::Nil;
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot index into an integer")]
+    fn test_index_type_mismatch() {
+        use value::Value;
+
+        let mut value = Value::Int(1);
+        value["key"] = Value::Nil;
+    }
+}
@@ -0,0 +1,103 @@
+use super::Value;
+
+impl Value {
+    /// Applies `patch` like a JSON merge patch (RFC 7386).
+    ///
+    /// A map patch is merged into the entries of `self` recursively: a `nil` value removes the
+    /// entry, and any other value is merged into it, so that keys of any type can be patched.
+    /// If `self` isn't a map, it is replaced with an empty one first. Any other patch, including
+    /// an array, replaces `self` entirely.
+    pub fn merge(&mut self, patch: &Value) {
+        let patch = match patch {
+            Value::Map(patch) => patch,
+            _ => {
+                *self = patch.clone();
+                return;
+            }
+        };
+        match self {
+            Value::Map(_) => {}
+            _ => *self = Value::Map(Default::default()),
+        }
+        if let Value::Map(map) = self {
+            for (key, value) in patch.iter() {
+                if let Value::Nil = value {
+                    map.remove(key);
+                } else if let Some(target) = map.get_mut(key) {
+                    target.merge(value);
+                } else {
+                    let mut target = Value::Nil;
+                    target.merge(value);
+                    map.insert(key.clone(), target);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_merge() {
+        let mut config = sion!([
+            "name": "server",
+            "port": 80,
+            "tls": ["cert": "a.pem", "key": "a.key"],
+            "hosts": ["a", "b"],
+            1: "one",
+            nil: "nil key",
+            [1, 2]: "array key"
+        ]);
+        config.merge(&sion!([
+            "port": 8080,
+            "tls": ["key": nil, "ca": "ca.pem"],
+            "hosts": ["c"],
+            1: nil,
+            nil: "patched",
+            [1, 2]: ["nested": [1: nil, 2: 2]],
+            "debug": ["level": 1, "trace": nil]
+        ]));
+        let expected = sion!([
+            "name": "server",
+            "port": 8080,
+            "tls": ["cert": "a.pem", "ca": "ca.pem"],
+            "hosts": ["c"],
+            nil: "patched",
+            [1, 2]: ["nested": [2: 2]],
+            "debug": ["level": 1]
+        ]);
+        assert_eq!(config, expected);
+    }
+
+    #[test]
+    fn test_merge_replace() {
+        let mut value = sion!(["a": 1]);
+        value.merge(&sion!([1, 2]));
+        assert_eq!(value, sion!([1, 2]));
+        value.merge(&sion!(nil));
+        assert_eq!(value, sion!(nil));
+        value.merge(&sion!(["a": nil]));
+        assert_eq!(value, sion!([:]));
+
+        let mut value = sion!("scalar");
+        value.merge(&sion!(["a": ["b": 1]]));
+        assert_eq!(value, sion!(["a": ["b": 1]]));
+        value.merge(&sion!([:]));
+        assert_eq!(value, sion!(["a": ["b": 1]]));
+    }
+
+    #[test]
+    fn test_merge_reordered() {
+        use to_string;
+
+        let mut value = sion!(["a": 1, "b": ["x": 1, "y": 2], "c": 3]);
+        value.merge(&sion!(["c": 4, "d": 5, "b": ["y": 3, "x": nil]]));
+        let expected = sion!(["d": 5, "c": 4, "b": ["y": 3], "a": 1]);
+        assert_eq!(value, expected);
+        // existing entries keep their places and new ones are appended
+        assert_eq!(
+            to_string(&value).unwrap(),
+            r#"["a":1,"b":["y":3],"c":4,"d":5]"#
+        );
+    }
+}
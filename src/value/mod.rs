@@ -1,12 +1,43 @@
+mod borrowed;
+pub(crate) mod cmp;
+mod convert;
 pub mod de;
+mod index;
+mod merge;
+pub mod path;
 pub mod ser;
+pub mod visit;
+pub use self::borrowed::ValueRef;
+pub use self::de::from_value;
+pub use self::index::Index;
+pub use self::path::{Path, Segment};
+pub use self::ser::to_value;
 use sequence::{Array, Map};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use Error;
 
-#[derive(Debug, PartialEq)]
+/// A SION value.
+///
+/// Values are totally ordered, so that they can be used as keys of `BTreeMap` and `HashMap`:
+///
+/// - variants compare in the order `Nil < Bool < integers < Double < String < Data < Date < Array < Map`
+/// - the integer variants compare numerically with each other, so `Int(1) == UInt(1)`
+/// - for `Double` and `Date`, `NaN` equals itself and is greater than any other number,
+///   and `-0.0 == 0.0`
+/// - `Array` compares lexicographically; `Map` ignores the order of its entries and compares
+///   lexicographically as if they were sorted by key
+#[derive(Clone, Debug)]
 pub enum Value {
     Nil,
     Bool(bool),
     Int(i64),
+    // integers outside the range of i64 use the narrowest of the following that fits
+    UInt(u64),
+    Int128(i128),
+    UInt128(u128),
+    #[cfg(feature = "bigint")]
+    BigInt(::num_bigint::BigInt),
     Double(f64),
     String(String),
     Data(Vec<u8>),
@@ -15,9 +46,30 @@ pub enum Value {
     Map(Map),
 }
 
+// `{:#}` pretty-prints the value.
+impl Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let output = if f.alternate() {
+            ::to_string_pretty(self)
+        } else {
+            ::to_string(self)
+        };
+        f.write_str(&output.map_err(|_| fmt::Error)?)
+    }
+}
+
+impl FromStr for Value {
+    type Err = Error;
+
+    fn from_str(s: &str) -> ::Result<Value> {
+        ::from_str(s)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_serialize_simple() {
         use super::Value::*;
         use to_string;
@@ -35,6 +87,11 @@ mod tests {
             to_string(&Data(vec![192, 168, 0, 1])).unwrap(),
             ".Data(\"wKgAAQ==\")"
         );
+        assert_eq!(to_string(&Date(0.0)).unwrap(), ".Date(0.0)");
+        assert_eq!(
+            to_string(&Date(1_500_000_000.5)).unwrap(),
+            ".Date(1500000000.5)"
+        );
     }
 
     #[test]
@@ -51,7 +108,7 @@ mod tests {
                     Double(1.1),
                     String("one".into()),
                     Array(::sequence::Array { contents: vec![Int(1)] }),
-                    Map(::sequence::Map { contents: vec![(String("one".into()), Double(1.1))] }),
+                    Map(::sequence::Map { contents: vec![(String("one".into()), Double(1.1))].into_iter().collect() }),
                 ]
             })),
             (Nil, String("Unlike JSON and Property Lists,".into())),
@@ -59,11 +116,12 @@ mod tests {
             (Int(1), String("does accept".into())),
             (Double(1.1), String("non-String keys.".into())),
             (Array(::sequence::Array { contents: vec![] }), String("like".into())),
-            (Map(::sequence::Map { contents: vec![] }), String("Map of ECMAScript.".into())),
-        ]})).unwrap(), r#"["array":[nil,true,1,1.1,"one",[1],["one":1.1]],nil:"Unlike JSON and Property Lists,",true:"Yes, SION",1:"does accept",1.1:"non-String keys.",[]:"like",[:]:"Map of ECMAScript."]"#);
+            (Map(::sequence::Map::new()), String("Map of ECMAScript.".into())),
+        ].into_iter().collect()})).unwrap(), r#"["array":[nil,true,1,1.1,"one",[1],["one":1.1]],nil:"Unlike JSON and Property Lists,",true:"Yes, SION",1:"does accept",1.1:"non-String keys.",[]:"like",[:]:"Map of ECMAScript."]"#);
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_deserialize_simple() {
         use super::Value::{self, *};
         use from_str;
@@ -81,6 +139,11 @@ mod tests {
             from_str::<Value>(".Data(\"wKgAAQ==\")").unwrap(),
             Data(vec![192, 168, 0, 1])
         );
+        assert_eq!(from_str::<Value>(".Date(0.0)").unwrap(), Date(0.0));
+        assert_eq!(
+            from_str::<Value>(".Date(1500000000.5)").unwrap(),
+            Date(1_500_000_000.5)
+        );
     }
 
     #[test]
@@ -135,7 +198,9 @@ mod tests {
                                 contents: vec![Int(1)],
                             }),
                             Map(M {
-                                contents: vec![(String("one".into()), Double(1.0))],
+                                contents: vec![(String("one".into()), Double(1.0))]
+                                    .into_iter()
+                                    .collect(),
                             }),
                         ],
                     }),
@@ -159,9 +224,11 @@ mod tests {
                             (String("double".into()), Double(0.0)),
                             (String("int".into()), Int(0)),
                             (String("nil".into()), Nil),
-                            (String("object".into()), Map(M { contents: vec![] })),
+                            (String("object".into()), Map(M::new())),
                             (String("string".into()), String("".into())),
-                        ],
+                        ]
+                        .into_iter()
+                        .collect(),
                     }),
                 ),
                 (
@@ -181,11 +248,267 @@ mod tests {
                     String("like".into()),
                 ),
                 (
-                    Map(::sequence::Map { contents: vec![] }),
+                    Map(::sequence::Map::new()),
                     String("Map of ECMAScript.".into()),
                 ),
-            ],
+            ]
+            .into_iter()
+            .collect(),
         });
         assert_eq!(from_str::<Value>(input).unwrap(), expected);
     }
+
+    #[test]
+    fn test_display_and_from_str() {
+        use super::Value::{self, *};
+        use Error;
+
+        let value: Value = r#"["name": "sion", "tags": [1, .Date(0.0)], [:]: nil]"#
+            .parse()
+            .unwrap();
+        assert_eq!(
+            value.to_string(),
+            r#"["name":"sion","tags":[1,.Date(0.0)],[:]:nil]"#
+        );
+        let expected = r#"[
+    "name": "sion",
+    "tags": [
+        1,
+        .Date(0.0)
+    ],
+    [:]: nil
+]"#;
+        assert_eq!(format!("{:#}", value), expected);
+        assert_eq!(expected.parse::<Value>().unwrap(), value);
+        assert_eq!(format!("{}", Int(-1)), "-1");
+
+        match "[1, 2".parse::<Value>() {
+            Err(Error::Eof) => {}
+            v => panic!("{:?}", v),
+        }
+    }
+
+    #[test]
+    fn test_map_order() {
+        use super::Value::{self, *};
+        use {from_str, to_string};
+
+        let mut value = from_str::<Value>(r#"["b": 1, "a": 2, 1: 3, "b": 4]"#).unwrap();
+        assert_eq!(to_string(&value).unwrap(), r#"["b":4,"a":2,1:3]"#);
+        assert_eq!(value["b"], Int(4));
+        // equal regardless of the order
+        let reordered = from_str::<Value>(r#"["a": 2, "b": 4, 1: 3]"#).unwrap();
+        assert_eq!(value, reordered);
+        assert_ne!(to_string(&value).unwrap(), to_string(&reordered).unwrap());
+
+        if let Map(ref mut m) = value {
+            assert_eq!(m.remove(&String("a".into())), Some(Int(2)));
+            assert_eq!(m.insert(String("a".into()), Int(5)), None);
+            assert_eq!(m.get_index(0), Some((&String("b".into()), &Int(4))));
+        }
+        assert_eq!(to_string(&value).unwrap(), r#"["b":4,1:3,"a":5]"#);
+    }
+
+    #[test]
+    fn test_integers() {
+        use super::Value::{self, *};
+        use {from_str, to_string};
+
+        for &(input, ref expected) in &[
+            ("9223372036854775807", Int(i64::MAX)),
+            ("-9223372036854775808", Int(i64::MIN)),
+            ("9223372036854775808", UInt(1 << 63)),
+            ("0xFFFF_FFFF_FFFF_FFFF", UInt(u64::MAX)),
+            ("-9223372036854775809", Int128(-(1 << 63) - 1)),
+            (
+                "-170141183460469231731687303715884105728",
+                Int128(i128::MIN),
+            ),
+            ("18446744073709551616", UInt128(1 << 64)),
+            (
+                "340282366920938463463374607431768211455",
+                UInt128(u128::MAX),
+            ),
+        ] {
+            let value = from_str::<Value>(input).unwrap();
+            assert_eq!(&value, expected);
+            assert_eq!(
+                from_str::<Value>(&to_string(&value).unwrap()).unwrap(),
+                value
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "bigint"))]
+    fn test_integer_overflow() {
+        use super::Value;
+        use {from_str, Error};
+
+        for input in &[
+            "340282366920938463463374607431768211456",
+            "-170141183460469231731687303715884105729",
+            "0x1_0000_0000_0000_0000_0000_0000_0000_0000",
+        ] {
+            match from_str::<Value>(input) {
+                Err(Error::IntegerOverflow) => {}
+                v => panic!("{:?}", v),
+            }
+        }
+        match from_str::<u8>("256") {
+            Err(Error::DeserializeError(_)) => {}
+            v => panic!("{:?}", v),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "bigint")]
+    fn test_big_integer() {
+        use super::Value::{self, *};
+        use {from_str, to_string};
+
+        let input = "-0x1_0000_0000_0000_0000_0000_0000_0000_0000";
+        let expected = "-340282366920938463463374607431768211456";
+        let value = from_str::<Value>(input).unwrap();
+        assert_eq!(value, BigInt(expected.parse().unwrap()));
+        assert_eq!(to_string(&value).unwrap(), expected);
+        assert_eq!(::to_value(&value).unwrap(), value);
+        assert_eq!(::from_value::<Value>(value.clone()).unwrap(), value);
+        assert_eq!(
+            from_str::<Value>("[1: 340282366920938463463374607431768211456]").unwrap(),
+            from_str::<Value>("[1: 0x1_0000_0000_0000_0000_0000_0000_0000_0000]").unwrap(),
+        );
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Shape {
+        Empty,
+        Circle(f64),
+        Point(i64, i64),
+        Rect { width: i64, height: i64 },
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Config {
+        name: String,
+        port: Option<u16>,
+        created: ::Date,
+        shapes: Vec<Shape>,
+    }
+
+    #[test]
+    fn test_to_value() {
+        use super::Value::{self, *};
+        use serde::de::value::F32Deserializer;
+        use serde::de::IntoDeserializer;
+        use serde::Deserialize;
+        use {from_str, to_value, Date, Error};
+
+        let config = Config {
+            name: "sion".into(),
+            port: None,
+            created: Date::new(0.0),
+            shapes: vec![
+                Shape::Empty,
+                Shape::Circle(1.0),
+                Shape::Point(1, 2),
+                Shape::Rect {
+                    width: 3,
+                    height: 4,
+                },
+            ],
+        };
+        let expected = from_str::<Value>(
+            r#"[
+                "name": "sion",
+                "port": nil,
+                "created": .Date(0.0),
+                "shapes": ["Empty", ["Circle": 1.0], ["Point": [1, 2]], ["Rect": ["width": 3, "height": 4]]]
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(to_value(&config).unwrap(), expected);
+
+        assert_eq!(to_value(&u64::MAX).unwrap(), UInt(u64::MAX));
+        assert_eq!(to_value(&1_u64).unwrap(), Int(1));
+        assert_eq!(to_value(&-1_i128).unwrap(), Int(-1));
+        assert_eq!(to_value(&'c').unwrap(), String("c".into()));
+        assert_eq!(to_value(&0.1_f32).unwrap(), Double(0.1));
+        assert_eq!(
+            to_value(&[0.1_f32, 1e-40, -3e38]).unwrap(),
+            from_str::<Value>(&::to_string(&[0.1_f32, 1e-40, -3e38]).unwrap()).unwrap()
+        );
+        assert!(match to_value(&f32::NAN).unwrap() {
+            Double(f) => f.is_nan(),
+            _ => false,
+        });
+
+        // an f32 becomes the same value whichever way it comes
+        #[derive(Serialize)]
+        struct Reading {
+            celsius: f32,
+        }
+        let reading = to_value(&Reading { celsius: 0.1 }).unwrap();
+        assert_eq!(reading, from_str::<Value>(r#"["celsius": 0.1]"#).unwrap());
+        let f32_deserializer: F32Deserializer<Error> = 0.1_f32.into_deserializer();
+        let visited = Value::deserialize(f32_deserializer).unwrap();
+        assert_eq!(visited, to_value(&0.1_f32).unwrap());
+    }
+
+    #[test]
+    fn test_from_value() {
+        use super::Value;
+        use serde::de::IntoDeserializer;
+        use serde::Deserialize;
+        use std::collections::BTreeMap;
+        use {from_str, from_value, to_value, Date, Error};
+
+        let input = r#"[
+            "name": "sion",
+            "port": 8080,
+            "created": .Date(1.5),
+            "shapes": ["Empty", ["Empty": nil], ["Circle": 1.0], ["Point": [1, 2]], ["Rect": ["width": 3, "height": 4]]]
+        ]"#;
+        let value = from_str::<Value>(input).unwrap();
+        let config = Config {
+            name: "sion".into(),
+            port: Some(8080),
+            created: Date::new(1.5),
+            shapes: vec![
+                Shape::Empty,
+                Shape::Empty,
+                Shape::Circle(1.0),
+                Shape::Point(1, 2),
+                Shape::Rect {
+                    width: 3,
+                    height: 4,
+                },
+            ],
+        };
+        assert_eq!(Config::deserialize(&value).unwrap(), config);
+        assert_eq!(from_value::<Config>(value.clone()).unwrap(), config);
+        assert_eq!(from_value::<Value>(value.clone()).unwrap(), value);
+        assert_eq!(to_value(&config).unwrap()["created"], value["created"]);
+
+        // borrows strings out of the tree
+        let value = from_str::<Value>(r#"["a": "b", "c": "d"]"#).unwrap();
+        let map = BTreeMap::<&str, &str>::deserialize(&value).unwrap();
+        assert_eq!(map["c"], "d");
+
+        let deserializer = Value::Int(1).into_deserializer();
+        assert_eq!(u8::deserialize(deserializer).unwrap(), 1);
+
+        match from_value::<Date>(Value::Double(1.5)) {
+            Err(Error::ExpectedDate) => {}
+            v => panic!("{:?}", v),
+        }
+        match from_value::<Shape>(from_str(r#"["Empty": nil, "Circle": 1.0]"#).unwrap()) {
+            Err(Error::UnexpectedEnumEntry) => {}
+            v => panic!("{:?}", v),
+        }
+        match from_value::<Shape>(Value::Int(1)) {
+            Err(Error::ExpectedEnum) => {}
+            v => panic!("{:?}", v),
+        }
+    }
 }
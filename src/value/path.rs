@@ -0,0 +1,258 @@
+use super::Value;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use {Error, Result};
+
+/// One step of a `Path`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Segment {
+    /// The entry of a map with this key. A string key that is a plain decimal number also
+    /// addresses that element of an array.
+    Key(Value),
+    /// An element of an array.
+    Index(usize),
+}
+
+/// A location in a `Value` tree, written like `/servers/0/port`.
+///
+/// Each segment is a string key (with `~` and `/` escaped as `~0` and `~1`) or an array index.
+/// A segment in parentheses is a SION literal, so non-string keys are written like `/(1)`,
+/// `/(nil)` or `/([1, 2])`. The empty path is the root.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Path {
+    segments: Vec<Segment>,
+}
+
+impl Path {
+    pub fn root() -> Self {
+        Default::default()
+    }
+
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    pub fn push(&mut self, segment: Segment) {
+        self.segments.push(segment)
+    }
+
+    pub fn pop(&mut self) -> Option<Segment> {
+        self.segments.pop()
+    }
+
+    /// This path extended by `segment`.
+    pub fn join(&self, segment: Segment) -> Self {
+        let mut path = self.clone();
+        path.push(segment);
+        path
+    }
+}
+
+// only canonical decimal numbers address array elements
+fn array_index(key: &str) -> Option<usize> {
+    let canonical = key == "0" || (!key.starts_with('0') && !key.is_empty());
+    if canonical && key.bytes().all(|b| b.is_ascii_digit()) {
+        key.parse().ok()
+    } else {
+        None
+    }
+}
+
+impl Segment {
+    fn child<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        match (self, v) {
+            (Segment::Index(i), Value::Array(a)) => a.get(*i),
+            (Segment::Key(Value::String(key)), Value::Array(a)) => a.get(array_index(key)?),
+            (Segment::Key(key), Value::Map(m)) => m.get(key),
+            _ => None,
+        }
+    }
+
+    fn child_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value> {
+        match (self, v) {
+            (Segment::Index(i), Value::Array(a)) => a.get_mut(*i),
+            (Segment::Key(Value::String(key)), Value::Array(a)) => a.get_mut(array_index(key)?),
+            (Segment::Key(key), Value::Map(m)) => m.get_mut(key),
+            _ => None,
+        }
+    }
+}
+
+impl Value {
+    /// Looks up a value by a path like `/servers/0/port`; see `Path` for the syntax.
+    ///
+    /// Returns `None` if the path is invalid or doesn't lead to a value.
+    pub fn pointer(&self, path: &str) -> Option<&Value> {
+        self.get_path(&path.parse().ok()?)
+    }
+
+    pub fn pointer_mut(&mut self, path: &str) -> Option<&mut Value> {
+        self.get_path_mut(&path.parse().ok()?)
+    }
+
+    pub fn get_path(&self, path: &Path) -> Option<&Value> {
+        path.segments
+            .iter()
+            .try_fold(self, |v, segment| segment.child(v))
+    }
+
+    pub fn get_path_mut(&mut self, path: &Path) -> Option<&mut Value> {
+        path.segments
+            .iter()
+            .try_fold(self, |v, segment| segment.child_mut(v))
+    }
+}
+
+impl FromStr for Path {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Path> {
+        let mut path = Path::root();
+        let mut rest = s;
+        while !rest.is_empty() {
+            rest = match rest.strip_prefix('/') {
+                Some(rest) => rest,
+                None => return Err(Error::ExpectedSlash),
+            };
+
+            if rest.starts_with('(') {
+                let mut values = ::StreamDeserializer::<Value>::new(&rest[1..]);
+                let key = values.next().unwrap_or(Err(Error::Eof))?;
+                rest = rest[1 + values.byte_offset()..].trim_start();
+                rest = match rest.strip_prefix(')') {
+                    Some(rest) => rest,
+                    None => return Err(Error::ExpectedCloseBracket),
+                };
+                if !rest.is_empty() && !rest.starts_with('/') {
+                    return Err(Error::ExpectedSlash);
+                }
+                path.push(Segment::Key(key));
+                continue;
+            }
+
+            let end = rest.find('/').unwrap_or(rest.len());
+            let mut key = String::new();
+            let mut chars = rest[..end].chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    '~' => match chars.next() {
+                        Some('0') => key.push('~'),
+                        Some('1') => key.push('/'),
+                        Some(c) => return Err(Error::UnexpectedEscape(c)),
+                        None => return Err(Error::Eof),
+                    },
+                    c => key.push(c),
+                }
+            }
+            path.push(Segment::Key(Value::String(key)));
+            rest = &rest[end..];
+        }
+        Ok(path)
+    }
+}
+
+impl Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Segment::Index(i) => write!(f, "{}", i),
+            // a plain key can't start with `(`
+            Segment::Key(Value::String(key)) if !key.starts_with('(') => {
+                write!(f, "{}", key.replace('~', "~0").replace('/', "~1"))
+            }
+            Segment::Key(key) => {
+                let literal = ::to_string(key).map_err(|_| fmt::Error)?;
+                write!(f, "({})", literal)
+            }
+        }
+    }
+}
+
+impl Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for segment in &self.segments {
+            write!(f, "/{}", segment)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_pointer() {
+        use value::Value::*;
+
+        let value = sion!([
+            "servers": [["host": "a", "port": 80], ["host": "b", "port": 8080]],
+            "a/b~c": 1,
+            "(x)": 2,
+            "0": 3,
+            1: "one",
+            nil: "nil",
+            true: ["nested": "yes"],
+            [1, 2]: "array",
+            "a/b": 4
+        ]);
+        assert_eq!(value.pointer(""), Some(&value));
+        assert_eq!(value.pointer("/servers/1/port"), Some(&Int(8080)));
+        assert_eq!(value.pointer("/servers/0/host"), Some(&String("a".into())));
+        assert_eq!(value.pointer("/a~1b~0c"), Some(&Int(1)));
+        assert_eq!(value.pointer("/(\"(x)\")"), Some(&Int(2)));
+        assert_eq!(value.pointer("/0"), Some(&Int(3)));
+        assert_eq!(value.pointer("/(1)"), Some(&String("one".into())));
+        assert_eq!(value.pointer("/(nil)"), Some(&String("nil".into())));
+        assert_eq!(
+            value.pointer("/( true )/nested"),
+            Some(&String("yes".into()))
+        );
+        assert_eq!(value.pointer("/([1, 2])"), Some(&String("array".into())));
+        assert_eq!(value.pointer("/(\"a/b\")"), Some(&Int(4)));
+
+        assert_eq!(value.pointer("/servers/2"), None);
+        assert_eq!(value.pointer("/servers/01"), None);
+        assert_eq!(value.pointer("/1"), None);
+        assert_eq!(value.pointer("servers"), None);
+        assert_eq!(value.pointer("/(1"), None);
+        assert_eq!(value.pointer("/~2"), None);
+    }
+
+    #[test]
+    fn test_pointer_mut() {
+        use value::Value::*;
+
+        let mut value = sion!(["servers": [["port": 80]], 1: nil]);
+        *value.pointer_mut("/servers/0/port").unwrap() = Int(8080);
+        *value.pointer_mut("/(1)").unwrap() = Bool(true);
+        assert_eq!(value, sion!(["servers": [["port": 8080]], 1: true]));
+        assert!(value.pointer_mut("/missing").is_none());
+    }
+
+    #[test]
+    fn test_path() {
+        use value::path::{Path, Segment};
+        use value::Value::*;
+        use Error;
+
+        let path = Path::root()
+            .join(Segment::Key(String("a/b".into())))
+            .join(Segment::Index(0))
+            .join(Segment::Key(String("(".into())))
+            .join(Segment::Key(Int(1)))
+            .join(Segment::Key(Nil));
+        assert_eq!(path.to_string(), "/a~1b/0/(\"(\")/(1)/(nil)");
+        let parsed = path.to_string().parse::<Path>().unwrap();
+        assert_eq!(parsed.segments()[0], path.segments()[0]);
+        assert_eq!(parsed.segments()[1], Segment::Key(String("0".into())));
+        assert_eq!(parsed.segments()[2..], path.segments()[2..]);
+        assert_eq!("".parse::<Path>().unwrap(), Path::root());
+
+        match "/(1)x".parse::<Path>() {
+            Err(Error::ExpectedSlash) => {}
+            v => panic!("{:?}", v),
+        }
+        match "/(1".parse::<Path>() {
+            Err(Error::ExpectedCloseBracket) => {}
+            v => panic!("{:?}", v),
+        }
+    }
+}
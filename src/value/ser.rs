@@ -1,20 +1,38 @@
+use super::de::integer;
 use super::Value;
-use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+use number::ParseResult;
+use sequence::{Array, Map};
+use serde::ser::{self, Serialize, SerializeMap as _, SerializeSeq as _};
+use {Error, Result};
+
+// the double written the same as `v`, so that `0.1f32` becomes `0.1` as in the text
+pub(super) fn widen(v: f32) -> f64 {
+    if v.is_finite() {
+        v.to_string().parse().unwrap()
+    } else {
+        f64::from(v)
+    }
+}
 
 impl Serialize for Value {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
     where
-        S: Serializer,
+        S: ser::Serializer,
     {
         use self::Value::*;
         match self {
             Nil => serializer.serialize_none(),
             Bool(b) => serializer.serialize_bool(*b),
             Int(i) => serializer.serialize_i64(*i),
+            UInt(u) => serializer.serialize_u64(*u),
+            Int128(i) => serializer.serialize_i128(*i),
+            UInt128(u) => serializer.serialize_u128(*u),
+            #[cfg(feature = "bigint")]
+            BigInt(b) => serializer.serialize_newtype_struct(::number::BIGINT_NAME, &b.to_string()),
             Double(f) => serializer.serialize_f64(*f),
             String(s) => serializer.serialize_str(s),
             Data(v) => serializer.serialize_bytes(v),
-            Date(_) => unimplemented!(),
+            Date(d) => ::date::Date::new(*d).serialize(serializer),
             Array(::sequence::Array { contents: v }) => {
                 let mut seq = serializer.serialize_seq(Some(v.len()))?;
                 for x in v.iter() {
@@ -32,3 +50,386 @@ impl Serialize for Value {
         }
     }
 }
+
+// a `Number` is parsed into a value like any literal, which doesn't keep how it was written
+#[cfg(feature = "arbitrary_precision")]
+fn is_number(name: &str) -> bool {
+    name == ::number::NUMBER_NAME
+}
+
+#[cfg(not(feature = "arbitrary_precision"))]
+fn is_number(_name: &str) -> bool {
+    false
+}
+
+/// Serializes `value` into a `Value` tree instead of text.
+pub fn to_value<T>(value: &T) -> Result<Value>
+where
+    T: Serialize + ?Sized,
+{
+    value.serialize(Serializer)
+}
+
+/// Serializer whose output is a `Value`.
+#[derive(Debug)]
+pub struct Serializer;
+
+impl ser::Serializer for Serializer {
+    type Ok = Value;
+    type Error = Error;
+
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeArray;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeMap;
+    type SerializeStructVariant = SerializeMap;
+
+    fn serialize_bool(self, v: bool) -> Result<Value> {
+        Ok(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value> {
+        Ok(Value::Int(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Value> {
+        Ok(integer(ParseResult::from_i128(v)))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value> {
+        self.serialize_u128(u128::from(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Value> {
+        Ok(integer(ParseResult::from_u128(v)))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value> {
+        self.serialize_f64(widen(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value> {
+        Ok(Value::Double(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value> {
+        Ok(Value::String(v.into()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value> {
+        Ok(Value::Data(v.into()))
+    }
+
+    fn serialize_none(self) -> Result<Value> {
+        Ok(Value::Nil)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Value>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value> {
+        Ok(Value::Nil)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Value>
+    where
+        T: Serialize + ?Sized,
+    {
+        #[cfg(feature = "bigint")]
+        {
+            if name == ::number::BIGINT_NAME {
+                return match value.serialize(self)? {
+                    Value::String(digits) => digits
+                        .parse()
+                        .map(Value::BigInt)
+                        .map_err(|_| Error::Expected("integer".into())),
+                    _ => Err(Error::Expected("integer".into())),
+                };
+            }
+        }
+        if name == ::date::NAME {
+            match value.serialize(self)? {
+                Value::Double(d) => Ok(Value::Date(d)),
+                _ => Err(Error::Expected("Double".into())),
+            }
+        } else if name == ::raw::NAME || is_number(name) {
+            match value.serialize(self)? {
+                Value::String(sion) => ::from_str(&sion),
+                _ => Err(Error::Expected("a SION value".into())),
+            }
+        } else {
+            value.serialize(self)
+        }
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value>
+    where
+        T: Serialize + ?Sized,
+    {
+        let mut map = Map::new();
+        map.insert(Value::String(variant.into()), value.serialize(self)?);
+        Ok(Value::Map(map))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeArray> {
+        Ok(SerializeArray {
+            array: Vec::with_capacity(len.unwrap_or(0)),
+            variant: None,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeArray> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SerializeArray> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray> {
+        Ok(SerializeArray {
+            array: Vec::with_capacity(len),
+            variant: Some(variant),
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeMap> {
+        Ok(SerializeMap {
+            map: Map::new(),
+            key: None,
+            variant: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeMap> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SerializeMap> {
+        Ok(SerializeMap {
+            map: Map::new(),
+            key: None,
+            variant: Some(variant),
+        })
+    }
+}
+
+// wraps the value of an enum variant as `[variant: value]`
+fn with_variant(variant: Option<&'static str>, value: Value) -> Value {
+    match variant {
+        Some(variant) => {
+            let mut map = Map::new();
+            map.insert(Value::String(variant.into()), value);
+            Value::Map(map)
+        }
+        None => value,
+    }
+}
+
+#[derive(Debug)]
+pub struct SerializeArray {
+    array: Vec<Value>,
+    variant: Option<&'static str>,
+}
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.array.push(value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value> {
+        let array = Value::Array(Array {
+            contents: self.array,
+        });
+        Ok(with_variant(self.variant, array))
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeArray {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+#[derive(Debug)]
+pub struct SerializeMap {
+    map: Map,
+    // the key waiting for its value
+    key: Option<Value>,
+    variant: Option<&'static str>,
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.key = Some(key.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = self
+            .key
+            .take()
+            .expect("serialize_value called before serialize_key");
+        self.map.insert(key, value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value> {
+        Ok(with_variant(self.variant, Value::Map(self.map)))
+    }
+}
+
+impl ser::SerializeStruct for SerializeMap {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        ser::SerializeMap::serialize_entry(self, key, value)
+    }
+
+    fn end(self) -> Result<Value> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeMap {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        ser::SerializeMap::serialize_entry(self, key, value)
+    }
+
+    fn end(self) -> Result<Value> {
+        ser::SerializeMap::end(self)
+    }
+}
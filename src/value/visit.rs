@@ -0,0 +1,451 @@
+use super::{Path, Segment, Value};
+use sequence::{Array, Map};
+use std::mem;
+
+/// A traversal of a `Value` tree that keeps track of the path to each value.
+///
+/// Each method defaults to calling the `walk_*` function of the same name, which visits the
+/// children. An implementation overrides the methods it's interested in and calls the `walk_*`
+/// function to keep descending. Start with `visit_value(&Path::root(), &value)`.
+pub trait Visit {
+    fn visit_value(&mut self, path: &Path, value: &Value) {
+        walk_value(self, path, value)
+    }
+
+    fn visit_array(&mut self, path: &Path, array: &Array) {
+        walk_array(self, path, array)
+    }
+
+    fn visit_map(&mut self, path: &Path, map: &Map) {
+        walk_map(self, path, map)
+    }
+
+    /// Called with the path of each map entry, before its value.
+    fn visit_key(&mut self, _path: &Path, _key: &Value) {}
+
+    /// Called for anything but arrays and maps.
+    fn visit_scalar(&mut self, _path: &Path, _value: &Value) {}
+}
+
+pub fn walk_value<V>(visitor: &mut V, path: &Path, value: &Value)
+where
+    V: Visit + ?Sized,
+{
+    match value {
+        Value::Array(array) => visitor.visit_array(path, array),
+        Value::Map(map) => visitor.visit_map(path, map),
+        _ => visitor.visit_scalar(path, value),
+    }
+}
+
+pub fn walk_array<V>(visitor: &mut V, path: &Path, array: &Array)
+where
+    V: Visit + ?Sized,
+{
+    for (i, value) in array.iter().enumerate() {
+        visitor.visit_value(&path.join(Segment::Index(i)), value);
+    }
+}
+
+pub fn walk_map<V>(visitor: &mut V, path: &Path, map: &Map)
+where
+    V: Visit + ?Sized,
+{
+    for (key, value) in map.iter() {
+        let path = path.join(Segment::Key(key.clone()));
+        visitor.visit_key(&path, key);
+        visitor.visit_value(&path, value);
+    }
+}
+
+/// Like `Visit`, but may change the values and keys in place.
+pub trait VisitMut {
+    fn visit_value_mut(&mut self, path: &Path, value: &mut Value) {
+        walk_value_mut(self, path, value)
+    }
+
+    fn visit_array_mut(&mut self, path: &Path, array: &mut Array) {
+        walk_array_mut(self, path, array)
+    }
+
+    fn visit_map_mut(&mut self, path: &Path, map: &mut Map) {
+        walk_map_mut(self, path, map)
+    }
+
+    /// Called with the path of each map entry, before its value.
+    ///
+    /// The entry keeps its position under the new key. If entries end up with the same key,
+    /// they are merged into one at the position of the first, holding the value of the last.
+    fn visit_key_mut(&mut self, _path: &Path, _key: &mut Value) {}
+
+    /// Called for anything but arrays and maps.
+    fn visit_scalar_mut(&mut self, _path: &Path, _value: &mut Value) {}
+}
+
+pub fn walk_value_mut<V>(visitor: &mut V, path: &Path, value: &mut Value)
+where
+    V: VisitMut + ?Sized,
+{
+    match value {
+        Value::Array(array) => visitor.visit_array_mut(path, array),
+        Value::Map(map) => visitor.visit_map_mut(path, map),
+        _ => visitor.visit_scalar_mut(path, value),
+    }
+}
+
+pub fn walk_array_mut<V>(visitor: &mut V, path: &Path, array: &mut Array)
+where
+    V: VisitMut + ?Sized,
+{
+    for (i, value) in array.iter_mut().enumerate() {
+        visitor.visit_value_mut(&path.join(Segment::Index(i)), value);
+    }
+}
+
+// The keys of an IndexMap can't be changed in place, so the visitor gets copies of them.
+// If any has changed, the entries are inserted again once all of them have been visited,
+// and `Map::insert` keeps the position of a key it already has.
+pub fn walk_map_mut<V>(visitor: &mut V, path: &Path, map: &mut Map)
+where
+    V: VisitMut + ?Sized,
+{
+    // indices of the renamed entries with their new keys
+    let mut renamed = vec![];
+    for i in 0..map.len() {
+        let (key, value) = map.contents.get_index_mut(i).unwrap();
+        let path = path.join(Segment::Key(key.clone()));
+        let mut new_key = key.clone();
+        visitor.visit_key_mut(&path, &mut new_key);
+        if new_key != *key {
+            renamed.push((i, new_key));
+        }
+        visitor.visit_value_mut(&path, value);
+    }
+    if renamed.is_empty() {
+        return;
+    }
+    let mut renamed = renamed.into_iter().peekable();
+    for (i, (key, value)) in mem::take(&mut map.contents).into_iter().enumerate() {
+        let key = match renamed.peek() {
+            Some(&(j, _)) if j == i => renamed.next().unwrap().1,
+            _ => key,
+        };
+        map.insert(key, value);
+    }
+}
+
+/// Like `VisitMut`, but takes each value by value and builds the tree anew from what the
+/// methods return, so that a value may be replaced with one of another kind.
+///
+/// Each method defaults to calling the function of the same name in this module. Keys that
+/// end up equal are merged as in `VisitMut::visit_key_mut`.
+pub trait Fold {
+    fn fold_value(&mut self, path: &Path, value: Value) -> Value {
+        fold_value(self, path, value)
+    }
+
+    fn fold_array(&mut self, path: &Path, array: Array) -> Value {
+        fold_array(self, path, array)
+    }
+
+    fn fold_map(&mut self, path: &Path, map: Map) -> Value {
+        fold_map(self, path, map)
+    }
+
+    /// Called with the path of each map entry, before its value.
+    fn fold_key(&mut self, _path: &Path, key: Value) -> Value {
+        key
+    }
+
+    /// Called for anything but arrays and maps.
+    fn fold_scalar(&mut self, _path: &Path, value: Value) -> Value {
+        value
+    }
+}
+
+pub fn fold_value<F>(folder: &mut F, path: &Path, value: Value) -> Value
+where
+    F: Fold + ?Sized,
+{
+    match value {
+        Value::Array(array) => folder.fold_array(path, array),
+        Value::Map(map) => folder.fold_map(path, map),
+        _ => folder.fold_scalar(path, value),
+    }
+}
+
+pub fn fold_array<F>(folder: &mut F, path: &Path, array: Array) -> Value
+where
+    F: Fold + ?Sized,
+{
+    let contents = array
+        .contents
+        .into_iter()
+        .enumerate()
+        .map(|(i, value)| folder.fold_value(&path.join(Segment::Index(i)), value))
+        .collect();
+    Value::Array(Array { contents })
+}
+
+pub fn fold_map<F>(folder: &mut F, path: &Path, map: Map) -> Value
+where
+    F: Fold + ?Sized,
+{
+    let mut folded = Map::with_capacity(map.len());
+    for (key, value) in map.contents {
+        let path = path.join(Segment::Key(key.clone()));
+        let key = folder.fold_key(&path, key);
+        let value = folder.fold_value(&path, value);
+        folded.insert(key, value);
+    }
+    Value::Map(folded)
+}
+
+/// A depth-first iterator over a `Value` tree, returned by `Value::walk`.
+///
+/// It yields each value with its path, parents before children. The key of each map entry is
+/// yielded right before its value with the same path, but isn't descended into.
+pub struct Walk<'a> {
+    // values to yield, the last first, and whether to descend into them
+    stack: Vec<(Path, &'a Value, bool)>,
+}
+
+impl<'a> Iterator for Walk<'a> {
+    type Item = (Path, &'a Value);
+
+    fn next(&mut self) -> Option<(Path, &'a Value)> {
+        let (path, value, descend) = self.stack.pop()?;
+        if descend {
+            match value {
+                Value::Array(array) => {
+                    for (i, v) in array.iter().enumerate().rev() {
+                        self.stack.push((path.join(Segment::Index(i)), v, true));
+                    }
+                }
+                Value::Map(map) => {
+                    for (k, v) in map.contents.iter().rev() {
+                        let path = path.join(Segment::Key(k.clone()));
+                        self.stack.push((path.clone(), v, true));
+                        self.stack.push((path, k, false));
+                    }
+                }
+                _ => {}
+            }
+        }
+        Some((path, value))
+    }
+}
+
+impl Value {
+    pub fn walk(&self) -> Walk<'_> {
+        Walk {
+            stack: vec![(Path::root(), self, true)],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_visit() {
+        use sequence::Map;
+        use value::visit::{walk_map, Visit};
+        use value::{Path, Value};
+
+        // strings that look like secrets, in keys or values
+        struct Secrets {
+            found: Vec<String>,
+            maps: usize,
+        }
+
+        impl Visit for Secrets {
+            fn visit_map(&mut self, path: &Path, map: &Map) {
+                self.maps += 1;
+                walk_map(self, path, map)
+            }
+
+            fn visit_key(&mut self, path: &Path, key: &Value) {
+                self.visit_scalar(path, key)
+            }
+
+            fn visit_scalar(&mut self, path: &Path, value: &Value) {
+                if let Value::String(s) = value {
+                    if s.starts_with("sk-") {
+                        self.found.push(path.to_string());
+                    }
+                }
+            }
+        }
+
+        let value = sion!([
+            "db": ["password": "sk-1", "port": 5432],
+            "tokens": ["a", "sk-2"],
+            "sk-3": nil,
+            1: [[:]]
+        ]);
+        let mut secrets = Secrets {
+            found: vec![],
+            maps: 0,
+        };
+        secrets.visit_value(&Path::root(), &value);
+        assert_eq!(secrets.found, vec!["/db/password", "/tokens/1", "/sk-3"]);
+        assert_eq!(secrets.maps, 3);
+    }
+
+    #[test]
+    fn test_visit_mut() {
+        use value::visit::{walk_value_mut, VisitMut};
+        use value::{Path, Segment, Value};
+
+        struct Rename;
+
+        impl VisitMut for Rename {
+            fn visit_key_mut(&mut self, _path: &Path, key: &mut Value) {
+                if let Value::String(s) = key {
+                    *s = s.replace('_', "-");
+                }
+            }
+
+            fn visit_value_mut(&mut self, path: &Path, value: &mut Value) {
+                match path.segments().last() {
+                    Some(Segment::Key(Value::String(key))) if key == "password" => {
+                        *value = Value::String("***".into())
+                    }
+                    _ => walk_value_mut(self, path, value),
+                }
+            }
+        }
+
+        let mut value = sion!([
+            "max_size": 1,
+            "db": ["password": "secret", "user_name": "x"],
+            "list": [["a_b": 2]],
+            1: "one"
+        ]);
+        Rename.visit_value_mut(&Path::root(), &mut value);
+        let expected = sion!([
+            "max-size": 1,
+            "db": ["password": "***", "user-name": "x"],
+            "list": [["a-b": 2]],
+            1: "one"
+        ]);
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn test_visit_mut_collision() {
+        use to_string;
+        use value::visit::VisitMut;
+        use value::{Path, Value};
+
+        struct Lowercase;
+
+        impl VisitMut for Lowercase {
+            fn visit_key_mut(&mut self, _path: &Path, key: &mut Value) {
+                if let Value::String(s) = key {
+                    *s = s.to_lowercase();
+                }
+            }
+        }
+
+        let mut value = sion!(["a": 1, "B": 2, "A": 3, "b": 4, "c": 5]);
+        Lowercase.visit_value_mut(&Path::root(), &mut value);
+        assert_eq!(to_string(&value).unwrap(), r#"["a":3,"b":4,"c":5]"#);
+
+        // a panicking visitor leaves the entries in place
+        struct Panic;
+
+        impl VisitMut for Panic {
+            fn visit_key_mut(&mut self, _path: &Path, key: &mut Value) {
+                *key = Value::Nil;
+            }
+
+            fn visit_scalar_mut(&mut self, _path: &Path, value: &mut Value) {
+                if *value == Value::Int(4) {
+                    panic!("four");
+                }
+                *value = Value::Bool(true);
+            }
+        }
+
+        let mut value = sion!(["a": 3, "b": 4, "c": 5]);
+        let result = ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(|| {
+            Panic.visit_value_mut(&Path::root(), &mut value)
+        }));
+        assert!(result.is_err());
+        assert_eq!(to_string(&value).unwrap(), r#"["a":true,"b":4,"c":5]"#);
+    }
+
+    #[test]
+    fn test_fold() {
+        use sequence::Array;
+        use to_string;
+        use value::visit::{fold_array, Fold};
+        use value::{Path, Value};
+
+        // unwraps single-element arrays, counts the numbers and drops the key prefix
+        struct Simplify {
+            numbers: usize,
+        }
+
+        impl Fold for Simplify {
+            fn fold_array(&mut self, path: &Path, array: Array) -> Value {
+                match fold_array(self, path, array) {
+                    Value::Array(ref a) if a.len() == 1 => a.get(0).unwrap().clone(),
+                    value => value,
+                }
+            }
+
+            fn fold_key(&mut self, _path: &Path, key: Value) -> Value {
+                match key {
+                    Value::String(ref s) if s.starts_with("x-") => Value::String(s[2..].into()),
+                    key => key,
+                }
+            }
+
+            fn fold_scalar(&mut self, _path: &Path, value: Value) -> Value {
+                if let Value::Int(_) = value {
+                    self.numbers += 1;
+                }
+                value
+            }
+        }
+
+        let value = sion!([
+            "x-hosts": ["a"],
+            "ports": [80, [443]],
+            "x-ports": [[8080]],
+            1: [[:]]
+        ]);
+        let mut simplify = Simplify { numbers: 0 };
+        let value = simplify.fold_value(&Path::root(), value);
+        assert_eq!(
+            to_string(&value).unwrap(),
+            r#"["hosts":"a","ports":8080,1:[:]]"#
+        );
+        assert_eq!(simplify.numbers, 3);
+    }
+
+    #[test]
+    fn test_walk() {
+        let value = sion!(["a": [1, [2]], [3]: nil, "b": [:]]);
+        let walked: Vec<_> = value
+            .walk()
+            .map(|(path, v)| format!("{} {}", path, v))
+            .collect();
+        let expected = vec![
+            r#" ["a":[1,[2]],[3]:nil,"b":[:]]"#,
+            "/a \"a\"",
+            "/a [1,[2]]",
+            "/a/0 1",
+            "/a/1 [2]",
+            "/a/1/0 2",
+            "/([3]) [3]",
+            "/([3]) nil",
+            "/b \"b\"",
+            "/b [:]",
+        ];
+        assert_eq!(walked, expected);
+        assert_eq!(sion!(1).walk().count(), 1);
+    }
+}